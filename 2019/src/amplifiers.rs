@@ -0,0 +1,180 @@
+use itertools::Itertools;
+use std::thread;
+use crate::intcode::IntCode;
+
+static DEBUG: bool = false;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Topology {
+    Serial,
+    Feedback,
+}
+
+pub struct AmplifierCircuit<'a> {
+    program: &'a str,
+    topology: Topology,
+}
+
+impl<'a> AmplifierCircuit<'a> {
+    pub fn serial(program: &'a str) -> AmplifierCircuit<'a> {
+        AmplifierCircuit { program, topology: Topology::Serial }
+    }
+
+    pub fn feedback(program: &'a str) -> AmplifierCircuit<'a> {
+        AmplifierCircuit { program, topology: Topology::Feedback }
+    }
+
+    // Runs one amplifier per phase setting, so the circuit is as long as the series. With no
+    // amplifiers the 0 going in is what comes out.
+    pub fn run(&self, phase_setting_series: &[i64]) -> i64 {
+        if phase_setting_series.is_empty() { return 0; }
+        let mut amplifiers = phase_setting_series.iter()
+            .map(|phase_setting| self.initialize_amplifier(*phase_setting))
+            .collect::<Vec<IntCode>>();
+        let mut signal = 0;
+
+        match self.topology {
+            Topology::Serial => {
+                for amplifier in amplifiers.iter_mut() {
+                    signal = AmplifierCircuit::amplify(amplifier, signal)
+                        .expect("Amplifier halted without producing a signal");
+                }
+            }
+            Topology::Feedback => {
+                let mut amplifier_reference = 0;
+                while let Some(output) = AmplifierCircuit::amplify(&mut amplifiers[amplifier_reference], signal) {
+                    signal = output;
+                    amplifier_reference = (amplifier_reference + 1) % amplifiers.len();
+                }
+            }
+        }
+        if DEBUG { println!("Series {:?} returns {}", phase_setting_series, signal) }
+        signal
+    }
+
+    // Tries every ordering of the phase settings and returns the one giving the highest signal.
+    // Ties go to the ordering that comes first, so the result doesn't depend on `parallel`.
+    pub fn find_best_phase_settings(&self, phase_settings: &[i64], parallel: bool) -> (Vec<i64>, i64) {
+        if phase_settings.is_empty() { return (vec![], self.run(&[])); }
+        let phase_setting_series = phase_settings.iter()
+            .cloned()
+            .permutations(phase_settings.len())
+            .collect::<Vec<Vec<i64>>>();
+
+        let best_index = if parallel {
+            self.find_best_series_in_parallel(&phase_setting_series)
+        } else {
+            self.find_best_series(&phase_setting_series, 0)
+        };
+
+        let (index, signal) = best_index.expect("No phase settings to try");
+        (phase_setting_series[index].clone(), signal)
+    }
+
+    fn find_best_series_in_parallel(&self, phase_setting_series: &[Vec<i64>]) -> Option<(usize, i64)> {
+        let number_of_threads = thread::available_parallelism().map_or(1, |x| x.get());
+        let chunk_size = phase_setting_series.len().div_ceil(number_of_threads).max(1);
+
+        thread::scope(|scope| {
+            let handles = phase_setting_series.chunks(chunk_size)
+                .enumerate()
+                .map(|(chunk_index, chunk)| {
+                    scope.spawn(move || self.find_best_series(chunk, chunk_index * chunk_size))
+                })
+                .collect::<Vec<_>>();
+
+            handles.into_iter()
+                .filter_map(|handle| handle.join().unwrap())
+                .fold(None, AmplifierCircuit::better)
+        })
+    }
+
+    fn find_best_series(&self, phase_setting_series: &[Vec<i64>], offset: usize) -> Option<(usize, i64)> {
+        phase_setting_series.iter()
+            .enumerate()
+            .map(|(index, series)| (offset + index, self.run(series)))
+            .fold(None, AmplifierCircuit::better)
+    }
+
+    fn better(best: Option<(usize, i64)>, candidate: (usize, i64)) -> Option<(usize, i64)> {
+        match best {
+            Some(best) if best.1 > candidate.1 || (best.1 == candidate.1 && best.0 < candidate.0) => Some(best),
+            _ => Some(candidate)
+        }
+    }
+
+    fn initialize_amplifier(&self, phase_setting: i64) -> IntCode {
+        let mut amplifier = IntCode::initialize(self.program, Some(phase_setting), true);
+        amplifier.pause_when_waiting_for_input();
+        amplifier.execute();
+        amplifier
+    }
+
+    fn amplify(amplifier: &mut IntCode, input_signal: i64) -> Option<i64> {
+        amplifier.set_input(input_signal);
+        amplifier.execute();
+        if amplifier.has_output() { Some(amplifier.take_output()[0]) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amplifiers::AmplifierCircuit;
+
+    #[test]
+    fn it_works() {
+        let best = AmplifierCircuit::serial("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0")
+            .find_best_phase_settings(&[0, 1, 2, 3, 4], false);
+        assert_eq!(best, (vec![4, 3, 2, 1, 0], 43210));
+
+        let best = AmplifierCircuit::serial("3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0")
+            .find_best_phase_settings(&[0, 1, 2, 3, 4], false);
+        assert_eq!(best, (vec![0, 1, 2, 3, 4], 54321));
+
+        let best = AmplifierCircuit::serial("3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0")
+            .find_best_phase_settings(&[0, 1, 2, 3, 4], false);
+        assert_eq!(best, (vec![1, 0, 4, 3, 2], 65210));
+    }
+
+    #[test]
+    fn it_works_for_feedback_loop() {
+        let best = AmplifierCircuit::feedback("3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5")
+            .find_best_phase_settings(&[5, 6, 7, 8, 9], false);
+        assert_eq!(best, (vec![9, 8, 7, 6, 5], 139629729));
+
+        let best = AmplifierCircuit::feedback("3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10")
+            .find_best_phase_settings(&[5, 6, 7, 8, 9], false);
+        assert_eq!(best, (vec![9, 7, 8, 5, 6], 18216));
+    }
+
+    #[test]
+    fn runs_circuits_of_any_length() {
+        let circuit = AmplifierCircuit::serial("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+        assert_eq!(circuit.run(&[1, 2, 3]), 123);
+        assert_eq!(circuit.find_best_phase_settings(&[0, 1, 2], false), (vec![2, 1, 0], 210));
+        assert_eq!(circuit.find_best_phase_settings(&[7], false), (vec![7], 7));
+    }
+
+    #[test]
+    fn runs_empty_circuits() {
+        let program = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+        for circuit in [AmplifierCircuit::serial(program), AmplifierCircuit::feedback(program)].iter() {
+            assert_eq!(circuit.run(&[]), 0);
+            assert_eq!(circuit.find_best_phase_settings(&[], false), (vec![], 0));
+            assert_eq!(circuit.find_best_phase_settings(&[], true), (vec![], 0));
+        }
+    }
+
+    #[test]
+    fn parallel_search_finds_the_same_phase_settings() {
+        let input = include_str!("../data/seven.data");
+
+        let serial = AmplifierCircuit::serial(input);
+        assert_eq!(serial.find_best_phase_settings(&[0, 1, 2, 3, 4], true),
+                   serial.find_best_phase_settings(&[0, 1, 2, 3, 4], false));
+
+        let feedback = AmplifierCircuit::feedback(input);
+        assert_eq!(feedback.find_best_phase_settings(&[5, 6, 7, 8, 9], true),
+                   feedback.find_best_phase_settings(&[5, 6, 7, 8, 9], false));
+    }
+}
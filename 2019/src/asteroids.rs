@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use num::integer::gcd;
+
+// Column and row on the map, so y grows downwards.
+pub type Asteroid = (i64, i64);
+
+// The step from one asteroid towards another, divided by the gcd of its coordinates.
+// Asteroids share a direction exactly when they are on the same line of sight.
+pub type Direction = (i64, i64);
+
+pub fn direction((x1, y1): Asteroid, (x2, y2): Asteroid) -> Direction {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let divisor = gcd(dx, dy).max(1);
+    (dx / divisor, dy / divisor)
+}
+
+// Orders directions clockwise starting from straight up. Directions from the right half,
+// straight up included, come before the left half, and within a half the cross product says
+// which one the laser reaches first. The cross product is taken in i128 so it can't overflow.
+pub fn clockwise_from_up(a: Direction, b: Direction) -> Ordering {
+    let is_left_half = |(dx, dy): Direction| dx < 0 || (dx == 0 && dy > 0);
+    is_left_half(a).cmp(&is_left_half(b)).then_with(|| {
+        let cross = a.0 as i128 * b.1 as i128 - a.1 as i128 * b.0 as i128;
+        0.cmp(&cross)
+    })
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Vaporization {
+    pub asteroid: Asteroid,
+    // Counts full turns of the laser, starting at 0.
+    pub rotation: usize,
+}
+
+// Asteroids in the order a laser turning clockwise from straight up vaporizes them. Each turn
+// hits the nearest remaining asteroid in every direction.
+pub struct VaporizationOrder {
+    lines_of_sight: Vec<VecDeque<Asteroid>>,
+    index: usize,
+    rotation: usize,
+}
+
+impl VaporizationOrder {
+    pub fn new(station: Asteroid, asteroids: &[Asteroid]) -> VaporizationOrder {
+        let mut by_direction: HashMap<Direction, Vec<Asteroid>> = HashMap::new();
+        asteroids.iter()
+            .filter(|x| **x != station)
+            .for_each(|x| by_direction.entry(direction(station, *x)).or_default().push(*x));
+
+        let mut directions = by_direction.into_iter().collect::<Vec<(Direction, Vec<Asteroid>)>>();
+        directions.sort_by(|a, b| clockwise_from_up(a.0, b.0));
+
+        let lines_of_sight = directions.into_iter()
+            .map(|(_, mut line)| {
+                line.sort_by_key(|x| (x.0 - station.0).abs() + (x.1 - station.1).abs());
+                line.into_iter().collect()
+            })
+            .collect();
+        VaporizationOrder { lines_of_sight, index: 0, rotation: 0 }
+    }
+}
+
+impl Iterator for VaporizationOrder {
+    type Item = Vaporization;
+
+    fn next(&mut self) -> Option<Vaporization> {
+        if self.index == self.lines_of_sight.len() {
+            self.lines_of_sight.retain(|x| !x.is_empty());
+            self.index = 0;
+            self.rotation += 1;
+        }
+
+        let asteroid = self.lines_of_sight.get_mut(self.index)?.pop_front()?;
+        self.index += 1;
+        Some(Vaporization { asteroid, rotation: self.rotation })
+    }
+}
+
+pub struct AsteroidMap {
+    asteroids: Vec<Asteroid>,
+}
+
+impl AsteroidMap {
+    pub fn new(asteroids: Vec<Asteroid>) -> AsteroidMap {
+        AsteroidMap { asteroids }
+    }
+
+    // Reads a map where `#` marks an asteroid, in reading order.
+    pub fn parse(map: &str) -> AsteroidMap {
+        let asteroids = map.lines()
+            .enumerate()
+            .flat_map(|(y, line)| line.chars()
+                .enumerate()
+                .filter(|&(_, x)| x == '#')
+                .map(move |(x, _)| (x as i64, y as i64)))
+            .collect();
+        AsteroidMap { asteroids }
+    }
+
+    pub fn asteroids(&self) -> &[Asteroid] {
+        &self.asteroids
+    }
+
+    pub fn visible_from(&self, station: Asteroid) -> usize {
+        self.asteroids.iter()
+            .filter(|x| **x != station)
+            .map(|x| direction(station, *x))
+            .collect::<HashSet<Direction>>()
+            .len()
+    }
+
+    pub fn vaporization_order(&self, station: Asteroid) -> VaporizationOrder {
+        VaporizationOrder::new(station, &self.asteroids)
+    }
+
+    // The asteroid that sees the most others, with how many it sees. Ties go to the
+    // asteroid that comes first on the map.
+    pub fn best_station(&self) -> Option<(Asteroid, usize)> {
+        self.asteroids.iter()
+            .map(|x| (*x, self.visible_from(*x)))
+            .fold(None, |best, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asteroids::{clockwise_from_up, direction, AsteroidMap, Vaporization};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn reduces_directions() {
+        assert_eq!(direction((0, 0), (2, 2)), (1, 1));
+        assert_eq!(direction((1, 0), (3, 4)), (1, 2));
+        assert_eq!(direction((5, 5), (5, 0)), (0, -1));
+        assert_eq!(direction((5, 5), (-7, 2)), (-4, -1));
+    }
+
+    #[test]
+    fn finds_best_station() {
+        assert_eq!(AsteroidMap::parse(".#..#\n.....\n#####\n....#\n...##").best_station(), Some(((3, 4), 8)));
+        assert_eq!(AsteroidMap::parse("......#.#.\n#..#.#....\n..#######.\n.#.#.###..\n.#..#.....\n..#....#.#\n#..#....#.\n.##.#..###\n##...#..#.\n.#....####").best_station(), Some(((5, 8), 33)));
+        assert_eq!(AsteroidMap::parse("").best_station(), None);
+    }
+
+    #[test]
+    fn works_far_beyond_puzzle_sizes() {
+        // All three directions are within a billionth of a radian of each other, which f32
+        // angles merge into one, and the first is shared by asteroids a trillion apart.
+        let station = (0, 0);
+        let mut asteroids = (1..1000).map(|x| (x * 1_000_000_007, x * 1_000_000_007 + x)).collect::<Vec<_>>();
+        asteroids.push((999_999_999, 1_000_000_000));
+        asteroids.push((1_000_000_000, 1_000_000_001));
+        asteroids.push(station);
+
+        assert_eq!(AsteroidMap::new(asteroids).visible_from(station), 3);
+    }
+
+    #[test]
+    fn orders_directions_clockwise_from_up() {
+        let mut directions = vec![(-1, -1), (0, 1), (1, 0), (-1, 0), (0, -1), (1, 1), (-1, 1), (1, -1), (1, -3)];
+        directions.sort_by(|a, b| clockwise_from_up(*a, *b));
+        assert_eq!(directions, vec![(0, -1), (1, -3), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)]);
+        assert_eq!(clockwise_from_up((1_000_000_000, -1), (1_000_000_001, -1)), Ordering::Less);
+    }
+
+    #[test]
+    fn vaporizes_clockwise_rotation_by_rotation() {
+        let map = AsteroidMap::parse(".#....#####...#..\n##...##.#####..##\n##...#...#.#####.\n..#.....#...###..\n..#.#.....#....##");
+        let order = map.vaporization_order((8, 3)).collect::<Vec<Vaporization>>();
+
+        assert_eq!(order.len(), map.asteroids().len() - 1);
+        assert_eq!(order.iter().take(9).map(|x| x.asteroid).collect::<Vec<_>>(),
+                   vec![(8, 1), (9, 0), (9, 1), (10, 0), (9, 2), (11, 1), (12, 1), (11, 2), (15, 1)]);
+        assert_eq!(order.iter().skip(30).map(|x| x.asteroid).collect::<Vec<_>>(),
+                   vec![(8, 0), (10, 1), (14, 0), (16, 1), (13, 3), (14, 3)]);
+        assert_eq!(order[0].rotation, 0);
+        assert_eq!(order.last().unwrap().rotation, 2);
+    }
+
+    #[test]
+    fn vaporizes_from_any_station() {
+        let map = AsteroidMap::parse(".#..##.###...#######\n##.############..##.\n.#.######.########.#\n.###.#######.####.#.\n#####.##.#.##.###.##\n..#####..#.#########\n####################\n#.####....###.#.#.##\n##.#################\n#####.##.###..####..\n..######..##.#######\n####.##.####...##..#\n.#####..#.######.###\n##...#.##########...\n#.##########.#######\n.####.#.###.###.#.##\n....##.##.###..#####\n.#.#.###########.###\n#.#.#.#####.####.###\n###.##.####.##.#..##");
+        let order = map.vaporization_order((11, 13)).map(|x| x.asteroid).collect::<Vec<_>>();
+        assert_eq!(order[0], (11, 12));
+        assert_eq!(order[1], (12, 1));
+        assert_eq!(order[2], (12, 2));
+        assert_eq!(order[9], (12, 8));
+        assert_eq!(order[19], (16, 0));
+        assert_eq!(order[49], (16, 9));
+        assert_eq!(order[99], (10, 16));
+        assert_eq!(order[198], (9, 6));
+        assert_eq!(order[199], (8, 2));
+        assert_eq!(order[200], (10, 9));
+        assert_eq!(order[298], (11, 1));
+        assert_eq!(order.len(), 299);
+
+        // The first rotation from a corner matches sorting by angle from straight up.
+        for station in [(0, 0), (19, 19), (0, 19), (19, 0)] {
+            let first_rotation = map.vaporization_order(station)
+                .take_while(|x| x.rotation == 0)
+                .map(|x| x.asteroid)
+                .collect::<Vec<_>>();
+            let mut expected = first_rotation.clone();
+            let angle = |x: &(i64, i64)| {
+                let angle = ((x.0 - station.0) as f64).atan2((station.1 - x.1) as f64);
+                if angle < 0.0 { angle + 2.0 * std::f64::consts::PI } else { angle }
+            };
+            expected.sort_by(|a, b| angle(a).partial_cmp(&angle(b)).unwrap());
+            assert_eq!(first_rotation, expected);
+            assert_eq!(first_rotation.len(), map.visible_from(station));
+        }
+    }
+}
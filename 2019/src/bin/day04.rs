@@ -1,136 +1,83 @@
-use std::collections::HashMap;
-
-pub fn next_valid_password(num: i64) -> i64 {
-    let (_,
-        has_two_same_adjacent_digits,
-        has_digits_with_non_decreasing_value) = is_valid(num);
-
-    match (has_digits_with_non_decreasing_value, has_two_same_adjacent_digits) {
-        (true, false) => {
-            return num + 10;
-        }
-        (false, _) => {
-            return repair_non_decreasing_order_of_values(num);
-        }
-        (true, true) => { num }
-    }
+use std::env;
+use std::ops::RangeInclusive;
+use advent_of_code::passwords::PasswordRules;
+
+const PUZZLE_RANGE: RangeInclusive<u64> = 357253..=892942;
+const PART_ONE_RULES: &str = "length 6, non-decreasing, adjacent-pair";
+const PART_TWO_RULES: &str = "length 6, non-decreasing, exact-pair";
+
+// `--range 100000-999999 --rules "length 6, exact-pair" --list` or `--sample 10`
+// checks other ranges and rules than the puzzle's.
+struct Options {
+    range: RangeInclusive<u64>,
+    rules: Option<String>,
+    list: bool,
+    sample: Option<usize>,
 }
 
-pub fn repair_non_decreasing_order_of_values(num: i64) -> i64 {
-    let mut digits: Vec<u32> = num.to_string().chars().map(|d| d.to_digit(10).unwrap()).collect();
-
-    let mut previous_digit = digits[0];
-    let mut start_repairing = false;
-    let updated_digits = digits.iter_mut().map(|digit| {
-        if start_repairing {
-            return previous_digit;
-        } else if previous_digit > *digit {
-            start_repairing = true;
-            return previous_digit;
+impl Options {
+    fn from_args<I: Iterator<Item = String>>(mut args: I) -> Options {
+        let mut options = Options { range: PUZZLE_RANGE, rules: None, list: false, sample: None };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().unwrap_or_else(|| panic!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--range" => options.range = parse_range(&value()),
+                "--rules" => options.rules = Some(value()),
+                "--sample" => options.sample = Some(value().parse().unwrap()),
+                "--list" => options.list = true,
+                _ => panic!("Unknown option {}", arg)
+            }
         }
-        previous_digit = *digit;
-        return *digit;
-    });
-
-    updated_digits.fold(String::from(""), |agg: String, x| -> String {
-        format!("{}{}", agg, x.to_string())
-    }).parse::<i64>().unwrap()
+        options
+    }
 }
 
-pub fn has_exactly_two_same_adjacent_digits (num : i64) -> bool {
-    let mut digits: Vec<u32> = num.to_string().chars().map(|d| d.to_digit(10).unwrap()).collect();
-    let count_map: HashMap<u32, u32> = digits.iter().fold(HashMap::new(), |mut map:  HashMap<u32, u32>, x| -> HashMap<u32, u32> {
-        if map.get(x).is_some() {
-            map.insert(*x, map.get(x).unwrap() + 1);
-        } else {
-            map.insert(*x, 1);
-        }
-        map
-    });
-
-    count_map.values().find(|x| **x == 2 as u32).is_some()
+fn parse_range(range: &str) -> RangeInclusive<u64> {
+    let (start, end) = range.split_once('-').unwrap_or_else(|| panic!("Expected a range like 1-100, got {}", range));
+    start.parse().unwrap()..=end.parse().unwrap()
 }
 
-pub fn is_valid(num: i64) -> (bool, bool, bool) {
-    let mut last_digit: i8 = (num % 10) as i8;
-    let mut remaining_digits: i64 = num / 10;
-
-    let mut number_of_digits = 1;
-    let mut has_two_same_adjacent_digits = false;
-    let mut has_digits_with_non_decreasing_value = true;
-
-    while remaining_digits > 0 {
-        let next_digit: i8 = (remaining_digits % 10) as i8;
-        if next_digit == last_digit {
-            has_two_same_adjacent_digits = true;
-        }
-
-        has_digits_with_non_decreasing_value =
-            has_digits_with_non_decreasing_value
-                && last_digit >= next_digit;
-
-        number_of_digits = number_of_digits + 1;
-        last_digit = next_digit;
-        remaining_digits = remaining_digits / 10;
+fn report(name: &str, rules: &PasswordRules, options: &Options) {
+    println!("{} {} passwords", name, rules.count(options.range.clone()));
+    if options.list {
+        println!("{}", rules.passwords(options.range.clone()).map(|x| x.to_string()).collect::<Vec<String>>().join(","));
+    }
+    if let Some(amount) = options.sample {
+        println!("Sample {:?}", rules.sample(options.range.clone(), amount, 2019));
     }
-
-    (number_of_digits == 6, has_two_same_adjacent_digits, has_digits_with_non_decreasing_value)
 }
 
 pub fn main() {
-    let mut stop = false;
-    let mut starting_range = 357253;
-    let mut all_possible_passwords: Vec<i64> = Vec::new();
-    while !stop {
-        let next_valid = next_valid_password(starting_range);
-        if next_valid <= 892942 {
-            starting_range = next_valid + 1;
-            all_possible_passwords.push(next_valid);
-        } else {
-            stop = true;
+    let options = Options::from_args(env::args().skip(1));
+
+    match &options.rules {
+        Some(rules) => report("Matching", &PasswordRules::parse(rules).unwrap(), &options),
+        None => {
+            report("All possible", &PasswordRules::parse(PART_ONE_RULES).unwrap(), &options);
+            report("Possible with additional validation", &PasswordRules::parse(PART_TWO_RULES).unwrap(), &options);
         }
     }
-    println!("All possible {} passwords {}", all_possible_passwords.len(), all_possible_passwords.iter()
-        .map(|x| x.to_string()).collect::<Vec<String>>().join(","));
-
-    all_possible_passwords = all_possible_passwords.iter()
-        .filter(|x| has_exactly_two_same_adjacent_digits(**x)).cloned().collect();
-    println!("Possible passwords with additional validation ({}) : {}", all_possible_passwords.len(), all_possible_passwords.iter()
-        .map(|x| x.to_string()).collect::<Vec<String>>().join(","));
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{is_valid, repair_non_decreasing_order_of_values, next_valid_password, has_exactly_two_same_adjacent_digits};
+    use crate::{parse_range, Options, PART_ONE_RULES, PART_TWO_RULES, PUZZLE_RANGE};
+    use advent_of_code::passwords::PasswordRules;
 
     #[test]
-    fn checks_is_valid() {
-        assert_eq!(is_valid(111111), (true, true, true));
-        assert_eq!(is_valid(223450), (true, true, false));
-        assert_eq!(is_valid(123789), (true, false, true));
-        assert_eq!(is_valid(12378), (false, false, true));
+    fn counts_puzzle_passwords() {
+        assert_eq!(PasswordRules::parse(PART_ONE_RULES).unwrap().count(PUZZLE_RANGE), 530);
+        assert_eq!(PasswordRules::parse(PART_TWO_RULES).unwrap().count(PUZZLE_RANGE), 324);
     }
 
     #[test]
-    fn should_repair_increasing_order_of_values() {
-        assert_eq!(repair_non_decreasing_order_of_values(25933), 25999);
-        assert_eq!(repair_non_decreasing_order_of_values(25999), 25999);
-        assert_eq!(repair_non_decreasing_order_of_values(10000), 11111);
-        assert_eq!(repair_non_decreasing_order_of_values(11121), 11122);
+    fn reads_options() {
+        let args = vec!["--range", "10-99", "--rules", "adjacent-pair", "--list", "--sample", "3"];
+        let options = Options::from_args(args.into_iter().map(String::from));
+        assert_eq!(options.range, 10..=99);
+        assert_eq!(options.rules, Some(String::from("adjacent-pair")));
+        assert!(options.list);
+        assert_eq!(options.sample, Some(3));
+        assert_eq!(parse_range("0-5"), 0..=5);
     }
-
-    #[test]
-    fn get_next_valid_password() {
-        assert_eq!(next_valid_password(357253), 357777);
-        assert_eq!(next_valid_password(223450), 223455);
-        assert_eq!(next_valid_password(123789), 123799);
-        assert_eq!(next_valid_password(123781), 123788);
-    }
-
-    #[test]
-    fn check_for_exactly_two_same_adjacent_digits() {
-        assert_eq!(has_exactly_two_same_adjacent_digits(112233), true);
-        assert_eq!(has_exactly_two_same_adjacent_digits(123444), false);
-        assert_eq!(has_exactly_two_same_adjacent_digits(111122), true);
-    }
-}
\ No newline at end of file
+}
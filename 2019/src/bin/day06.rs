@@ -1,154 +1,130 @@
-use std::str::Lines;
-use std::collections::HashMap;
-use std::f32::MAX;
-use std::cmp::min;
-
-
-pub fn get_all_orbits(orbits: &HashMap<String, String>) -> i64 {
-    orbits.keys().fold(0, |agg, x| {
-        let distance = distance_from_center_of_mass(orbits, String::from(x));
-        agg + distance
-    })
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use advent_of_code::orbits::OrbitMap;
+use advent_of_code::search::bfs;
+
+pub fn get_all_orbits(orbits: &OrbitMap) -> i64 {
+    orbits.total_orbits() as i64
 }
 
-pub fn distance_between_two_object(orbits: &HashMap<String, String>,
-                                   space_object1: String,
-                                   space_object2: String, ) -> i64 {
-    let mut path1: Vec<String> = path_towards_com(orbits, space_object1.as_str());
-    let mut path2: Vec<String> = path_towards_com(orbits, space_object2.as_str());
-    path1.reverse();
-    path2.reverse();
-
-    let mut joining_index = std::usize::MAX;
-    for (a, b) in path1.iter().zip(path2.iter()) {
-        if a != b {
-            joining_index = path2.iter().position(|x| x == b).unwrap();
-            break;
-        }
-    }
-    joining_index =
-        if joining_index == std::usize::MAX as usize { min(path1.len(), path2.len()) }
-        else { joining_index };
-
-    let number_of_orbital_transfer_in_path1 = path1.len() - 1;
-    let number_of_orbital_transfer_in_path2 = path2.len() - 1;
-    let common_orbital_transfer = joining_index - 1;
-
-    ((number_of_orbital_transfer_in_path1 + number_of_orbital_transfer_in_path2) -
-        ((common_orbital_transfer) * 2) - 2) as i64
+// Transfers between the objects the two are orbiting.
+pub fn distance_between_two_object(orbits: &OrbitMap, space_object1: &str, space_object2: &str) -> i64 {
+    let distance = orbits.distance(space_object1, space_object2)
+        .unwrap_or_else(|| panic!("{} or {} is not in the orbit map", space_object1, space_object2));
+    distance.saturating_sub(2) as i64
 }
 
-pub fn distance_from_center_of_mass(orbits: &HashMap<String, String>, space_object: String) -> i64 {
-    let mut center = orbits.get(space_object.as_str());
-    let mut total_distance = 1;
-    if center.is_none() {
-        if space_object != String::from("COM") {
-            panic!("Probably orbit map is wrong. Read {}", space_object)
-        } else {
-            return 0;
-        }
-    }
-
-    while center.unwrap() != "COM" {
-        total_distance += 1;
-        center = orbits.get(center.unwrap());
-    }
-    total_distance
+pub fn distance_from_center_of_mass(orbits: &OrbitMap, space_object: &str) -> i64 {
+    orbits.depth(space_object).unwrap_or_else(|| panic!("{} is not in the orbit map", space_object)) as i64
 }
 
-pub fn path_towards_com(orbits: &HashMap<String, String>, space_object: &str) -> Vec<String> {
-    let mut path: Vec<String> = Vec::new();
-    let mut center = orbits.get(space_object);
-    path.push(String::from(space_object));
-    if center.is_none() {
-        if space_object != String::from("COM") {
-            panic!("Probably orbit map is wrong. Read {}", space_object)
-        } else {
-            return path;
-        }
-    }
-
-    while center.unwrap() != "COM" {
-        path.push(String::from(center.unwrap()));
-        center = orbits.get(center.unwrap());
-    }
-    path.push(String::from(center.unwrap()));
-    path
+pub fn path_towards_com(orbits: &OrbitMap, space_object: &str) -> Vec<String> {
+    orbits.path_to_root(space_object)
+        .unwrap_or_else(|| panic!("{} is not in the orbit map", space_object))
+        .into_iter()
+        .map(String::from)
+        .collect()
 }
 
-pub fn read(maps: Lines) -> HashMap<String, String> {
-    let mut all_space_objects: HashMap<String, String> = HashMap::new();
-
-    for map in maps {
-        let split: Vec<String> = map.split(')').map(|s| s.to_string()).collect();
-        let center = &split[0];
-        let orbited_by = &split[1];
+// Objects passed moving from one object to the other, found by searching the orbits.
+pub fn transfer_route(orbits: &OrbitMap, space_object1: &str, space_object2: &str) -> Vec<String> {
+    bfs(orbits, space_object1.to_string())
+        .path_to(&space_object2.to_string())
+        .unwrap_or_else(|| panic!("{} or {} is not in the orbit map", space_object1, space_object2))
+}
 
-        let orbited_by_entry = all_space_objects.get(orbited_by);
-        if orbited_by_entry.is_none() {
-            all_space_objects.insert(String::from(orbited_by), String::from(center));
-        }
-    }
+pub fn read(maps: &str) -> OrbitMap {
+    OrbitMap::parse(maps).unwrap_or_else(|errors| {
+        panic!("Orbit map is wrong:\n{}", errors.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("\n"))
+    })
+}
 
-    return all_space_objects;
+fn output_path(default: &str) -> PathBuf {
+    env::args().nth(2).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(default))
 }
 
+// `dot [path]`, `json [path]` and `json-pretty [path]` export the map, `from-json <path>` solves a map exported
+// earlier and `route` prints the objects passed going from YOU to SAN.
 pub fn main() {
-    let contents = include_str!("../../data/six.data");
-    let orbits = read(contents.lines());
+    let orbits = match env::args().nth(1).as_deref() {
+        Some("from-json") => {
+            let path = env::args().nth(2).expect("Missing path to a JSON orbit map");
+            OrbitMap::from_json(&fs::read_to_string(path).unwrap()).unwrap_or_else(|errors| {
+                panic!("Orbit map is wrong:\n{}", errors.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("\n"))
+            })
+        }
+        _ => read(include_str!("../../data/six.data"))
+    };
 
     println!("Total number of orbits: {}", get_all_orbits(&orbits));
 
     println!("Distance between the objects SAN and YOU are orbiting: {}",
-             distance_between_two_object(&orbits, String::from("SAN"), String::from("YOU")))
+             distance_between_two_object(&orbits, "SAN", "YOU"));
+
+    match env::args().nth(1).as_deref() {
+        Some("dot") => {
+            let path = output_path("src/out/part6.dot");
+            orbits.save_dot(&path, Some(("YOU", "SAN"))).unwrap();
+            println!("Saved orbit map to {:?}", path);
+        }
+        Some("route") => println!("{}", transfer_route(&orbits, "YOU", "SAN").join(" -> ")),
+        Some(mode @ ("json" | "json-pretty")) => {
+            let path = output_path("src/out/part6.json");
+            orbits.save_json(&path, mode == "json-pretty").unwrap();
+            println!("Saved orbit map to {:?}", path);
+        }
+        _ => {}
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use crate::{read, distance_from_center_of_mass, get_all_orbits, path_towards_com, distance_between_two_object};
+    use crate::{read, distance_from_center_of_mass, get_all_orbits, path_towards_com, distance_between_two_object, transfer_route};
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
 
     #[test]
     fn calculates_distance() {
-        let map = read("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L".lines());
-        let b_distance = distance_from_center_of_mass(&map, String::from("B"));
-        let l_distance = distance_from_center_of_mass(&map, String::from("L"));
-        let d_distance = distance_from_center_of_mass(&map, String::from("D"));
-        assert_eq!(b_distance, 1);
-        assert_eq!(l_distance, 7);
-        assert_eq!(d_distance, 3);
+        let map = read(EXAMPLE);
+        assert_eq!(distance_from_center_of_mass(&map, "B"), 1);
+        assert_eq!(distance_from_center_of_mass(&map, "L"), 7);
+        assert_eq!(distance_from_center_of_mass(&map, "D"), 3);
     }
 
     #[test]
     fn derives_path() {
-        let map = read("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L".lines());
-        let b_path: Vec<String> = path_towards_com(&map, String::from("B").as_str());
-        let l_path: Vec<String> = path_towards_com(&map, String::from("L").as_str());
-        let d_path: Vec<String> = path_towards_com(&map, String::from("D").as_str());
-        assert_eq!(b_path.join("-"), "B-COM");
-        assert_eq!(l_path.join("-"), "L-K-J-E-D-C-B-COM");
-        assert_eq!(d_path.join("-"), "D-C-B-COM");
+        let map = read(EXAMPLE);
+        assert_eq!(path_towards_com(&map, "B").join("-"), "B-COM");
+        assert_eq!(path_towards_com(&map, "L").join("-"), "L-K-J-E-D-C-B-COM");
+        assert_eq!(path_towards_com(&map, "D").join("-"), "D-C-B-COM");
     }
 
     #[test]
     fn calculates_total_orbits() {
-        let map = read("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L".lines());
-        assert_eq!(get_all_orbits(&map), 42);
+        assert_eq!(get_all_orbits(&read(EXAMPLE)), 42);
     }
 
     #[test]
     fn calculates_distance_between_to_objects() {
-        let map = read("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L".lines());
-        assert_eq!(distance_between_two_object(&map,
-                                               String::from("K"),
-                                               String::from("I")), 2);
-
-        assert_eq!(distance_between_two_object(&map,
-                                               String::from("H"),
-                                               String::from("I")), 3);
-
-        assert_eq!(distance_between_two_object(&map,
-                                               String::from("L"),
-                                               String::from("B")), 4);
+        let map = read(EXAMPLE);
+        assert_eq!(distance_between_two_object(&map, "K", "I"), 2);
+        assert_eq!(distance_between_two_object(&map, "H", "I"), 3);
+        assert_eq!(distance_between_two_object(&map, "L", "B"), 4);
+    }
+
+    #[test]
+    fn finds_transfer_route() {
+        let map = read(&format!("{}\nK)YOU\nI)SAN", EXAMPLE));
+        assert_eq!(transfer_route(&map, "YOU", "SAN").join(")"), "YOU)K)J)E)D)I)SAN");
+        assert_eq!(transfer_route(&map, "L", "B").len(), 7);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn solves_puzzle() {
+        let map = read(include_str!("../../data/six.data"));
+        assert_eq!(get_all_orbits(&map), 140608);
+        assert_eq!(distance_between_two_object(&map, "SAN", "YOU"), 337);
+    }
+}
@@ -1,12 +0,0 @@
-use permutator::Permutation;
-use std::cmp::max;
-use std::collections::HashMap;
-
-static DEBUG: bool = false;
-
-pub fn main() {
-    let input = include_str!("../../data/seven.data");
-
-    //TODO REWRITE WITH use intcode::IntCode;
-}
-
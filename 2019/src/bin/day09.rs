@@ -1,5 +1,5 @@
 use std::str::Lines;
-use intcode::IntCode;
+use advent_of_code::intcode::IntCode;
 
 pub fn main() {
 
@@ -1,11 +1,16 @@
-use std::str::Lines;
-use intcode::IntCode;
-use std::collections::HashMap;
+use advent_of_code::intcode::IntCode;
+use std::collections::{HashMap, HashSet};
 use plotlib::scatter::Scatter;
 use plotlib::view::ContinuousView;
 use plotlib::scatter;
-use plotlib::style::Point;
 use plotlib::page::Page;
+use plotlib::style::Point;
+use std::env;
+use std::path::{Path, PathBuf};
+use image::{ImageBuffer, Rgb, RgbImage};
+use std::io;
+use advent_of_code::frames::{FrameRecorder, Palette, RecordingOptions};
+use advent_of_code::ocr::{self, OcrError};
 
 type Panel = (i32, i32);
 const UP: i32 = 0;
@@ -16,59 +21,169 @@ const LEFT: i32 = 3;
 const BLACK: i64 = 0;
 const WHITE: i64 = 1;
 
-pub fn main() {
+pub struct HullPainter<'a> {
+    program: &'a str,
+    starting_color: i64,
+}
 
-    let contents = include_str!("../../data/eleven.data");
+// Panels count y upwards, as the robot starts facing up.
+pub struct Hull {
+    pub panels: HashMap<Panel, i64>,
+    pub painted_panels: usize,
+}
+
+impl<'a> HullPainter<'a> {
+    pub fn new(program: &'a str, starting_color: i64) -> HullPainter<'a> {
+        HullPainter { program, starting_color }
+    }
+
+    pub fn paint(&self, mut recorder: Option<&mut FrameRecorder<i64>>) -> Hull {
+        let mut panels: HashMap<Panel, i64> = HashMap::new();
+        let mut painted: HashSet<Panel> = HashSet::new();
+        let mut current_panel = (0, 0);
+        let mut current_direction = UP;
+        panels.insert(current_panel, self.starting_color);
+
+        let mut intcode = IntCode::initialize(self.program, None, true);
+
+        loop {
+            let current_color = *panels.get(&current_panel).unwrap_or(&BLACK);
+
+            // Frames count y downwards.
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record(panels.iter().map(|(panel, color)| ((panel.0 as i64, -panel.1 as i64), *color)));
+            }
 
-    let intcode = paint_panels(contents);
+            intcode.set_input(current_color);
+            intcode.execute();
+            if !intcode.has_output() {
+                break
+            }
+            panels.insert(current_panel, intcode.take_output()[0]);
+            painted.insert(current_panel);
 
+            intcode.execute();
+            let direction_command = intcode.take_output()[0];
+
+            current_direction = turn_direction(current_direction, direction_command);
+            current_panel = move_panel(&current_panel, current_direction);
+        }
+
+        Hull { panels, painted_panels: painted.len() }
+    }
 }
 
-fn paint_panels(contents: &str) {
-    let mut panels: HashMap<Panel, i64> = HashMap::new();
-    let mut current_panel = (0, 0);
-    let mut current_direction = UP;
-    panels.insert(current_panel, WHITE);
-
-    let mut intcode = IntCode::initialize(
-        contents,
-        None,
-        true
-    );
-
-    loop {
-        let current_color_wrapped = panels.get(&current_panel);
-        let current_color =
-            if current_color_wrapped.is_some() { current_color_wrapped.unwrap() } else { &BLACK };
-
-        intcode.set_input(*current_color);
-        intcode.execute();
-        if !intcode.has_output() {
-            println!("No output exiting: {:?}", panels);
-            println!("count {}", panels.len());
-            let data2 = panels.iter()
-                .filter(|&x| *x.1 == 1)
-                .map(|x| (*x.0))
-                .map(|x| (x.0 as f64, x.1 as f64))
-                .collect::<Vec<(f64, f64)>>();
-            let scatter = Scatter::from_slice(&data2)
-                .style(scatter::Style::new()
-                    .colour("#35C788"));
-            let v = ContinuousView::new()
-                .add(&scatter)
-                .x_range(-44., 44.)
-                .y_range(-22., 22.);
-            Page::single(&v).save("./src/out/part11.svg");
-            println!("{}", Page::single(&v).to_text().unwrap());
-            break
+impl Hull {
+    fn white_panels(&self) -> impl Iterator<Item = Panel> + '_ {
+        self.panels.iter().filter(|(_, color)| **color == WHITE).map(|(panel, _)| *panel)
+    }
+
+    // (min_x, max_x, min_y, max_y) of the white panels, or None if there aren't any.
+    pub fn bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        let min_x = self.white_panels().map(|x| x.0).min()?;
+        let max_x = self.white_panels().map(|x| x.0).max()?;
+        let min_y = self.white_panels().map(|x| x.1).min()?;
+        let max_y = self.white_panels().map(|x| x.1).max()?;
+        Some((min_x, max_x, min_y, max_y))
+    }
+
+    pub fn to_ascii(&self) -> String {
+        ocr::grid_to_string(&self.to_grid())
+    }
+
+    // Rows run from the top of the hull down, covering the white panels.
+    pub fn to_grid(&self) -> Vec<Vec<bool>> {
+        let (min_x, max_x, min_y, max_y) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return vec![]
+        };
+
+        (min_y..=max_y).rev()
+            .map(|y| (min_x..=max_x).map(|x| self.panels.get(&(x, y)) == Some(&WHITE)).collect())
+            .collect()
+    }
+
+    pub fn registration_identifier(&self) -> Result<String, OcrError> {
+        ocr::recognize(&self.to_grid())
+    }
+
+    pub fn to_image(&self, scale: u32) -> RgbImage {
+        let (min_x, max_x, min_y, max_y) = self.bounds().unwrap_or((0, 0, 0, 0));
+        let width = (max_x - min_x + 1) as u32 * scale;
+        let height = (max_y - min_y + 1) as u32 * scale;
+
+        let mut image: RgbImage = ImageBuffer::from_pixel(width, height, Rgb([0, 0, 0]));
+        for (x, y) in self.white_panels() {
+            let left = (x - min_x) as u32 * scale;
+            let top = (max_y - y) as u32 * scale;
+            for image_x in left..left + scale {
+                for image_y in top..top + scale {
+                    image.put_pixel(image_x, image_y, Rgb([255, 255, 255]));
+                }
+            }
         }
-        panels.insert(current_panel, intcode.take_output()[0]);
+        image
+    }
+
+    pub fn save_png(&self, path: &Path, scale: u32) -> io::Result<()> {
+        self.to_image(scale).save(path)
+    }
 
-        intcode.execute();
-        let direction_command = intcode.take_output()[0];
+    pub fn save_svg(&self, path: &Path) -> Result<(), String> {
+        let (min_x, max_x, min_y, max_y) = self.bounds().unwrap_or((0, 0, 0, 0));
+        let data = self.white_panels()
+            .map(|x| (x.0 as f64, x.1 as f64))
+            .collect::<Vec<(f64, f64)>>();
+        let scatter = Scatter::from_slice(&data)
+            .style(scatter::Style::new()
+                .colour("#35C788"));
+        let view = ContinuousView::new()
+            .add(&scatter)
+            .x_range(min_x as f64 - 1., max_x as f64 + 1.)
+            .y_range(min_y as f64 - 1., max_y as f64 + 1.);
+        Page::single(&view).save(path).map_err(|x| x.to_string())
+    }
+}
+
+pub fn main() {
+
+    let contents = include_str!("../../data/eleven.data");
 
-        current_direction = turn_direction(current_direction, direction_command);
-        current_panel = move_panel(&current_panel, current_direction);
+    let hull = HullPainter::new(contents, BLACK).paint(None);
+    println!("Panels painted at least once {}", hull.painted_panels);
+
+    let default_path = |extension: &str| PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/out")
+        .join(format!("part11.{}", extension));
+    let path_argument = || env::args().nth(2).map(PathBuf::from);
+
+    let painter = HullPainter::new(contents, WHITE);
+    match env::args().nth(1).as_deref() {
+        Some("frames") => {
+            let options = RecordingOptions::from_args(env::args().skip(2));
+            let palette = Palette::new([0, 0, 0]).with(WHITE, [255, 255, 255]);
+            let mut recorder = FrameRecorder::with_options(palette, &options);
+            painter.paint(Some(&mut recorder));
+            let paths = recorder.save(&options, "part11").unwrap();
+            println!("Saved {} frames to {:?}", recorder.len(), paths[0]);
+        }
+        Some("svg") => {
+            let path = path_argument().unwrap_or_else(|| default_path("svg"));
+            painter.paint(None).save_svg(&path).unwrap();
+            println!("Saved registration identifier to {:?}", path);
+        }
+        Some("png") => {
+            let path = path_argument().unwrap_or_else(|| default_path("png"));
+            painter.paint(None).save_png(&path, 10).unwrap();
+            println!("Saved registration identifier to {:?}", path);
+        }
+        _ => {
+            let hull = painter.paint(None);
+            match hull.registration_identifier() {
+                Ok(identifier) => println!("Registration identifier {}", identifier),
+                Err(error) => println!("Registration identifier\n{}\n{}", hull.to_ascii(), error)
+            }
+        }
     }
 }
 
@@ -83,21 +198,46 @@ fn move_panel(current_panel: &(i32, i32), current_direction: i32) -> (i32, i32)
 }
 
 fn turn_direction(current_direction: i32, next_direction: i64) -> i32 {
-    let i = match next_direction {
+    match next_direction {
         0 => (4 + current_direction - 1) % 4,
         1 => (4 + current_direction + 1) % 4,
         _ => unreachable!()
-    };
-    i
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::{HullPainter, BLACK, WHITE};
+
+    // Reads a color and answers with the paint and turn commands from the puzzle example.
+    fn example_robot() -> String {
+        let commands = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+        let mut program = commands.iter()
+            .map(|(color, turn)| format!("3,100,104,{},104,{}", color, turn))
+            .collect::<Vec<String>>();
+        program.push(String::from("3,100,99"));
+        program.join(",")
+    }
 
-    #![test]
+    #[test]
+    fn paints_panels() {
+        let hull = HullPainter::new(&example_robot(), BLACK).paint(None);
 
-    use crate::paint_panels;
+        assert_eq!(hull.painted_panels, 6);
+        assert_eq!(hull.bounds(), Some((-1, 1, -1, 1)));
+        assert_eq!(hull.to_ascii(), "..#\n..#\n##.");
+    }
 
-    fn paints_panels() {
+    #[test]
+    fn counts_panels_painted_at_least_once() {
+        let input = include_str!("../../data/eleven.data");
+
+        let hull = HullPainter::new(input, BLACK).paint(None);
+        assert_eq!(hull.painted_panels, 1985);
+
+        let hull = HullPainter::new(input, WHITE).paint(None);
+        assert_eq!(hull.registration_identifier(), Ok(String::from("BLCZCJLZ")));
+        assert_eq!(hull.to_ascii().lines().count(), 6);
+        assert_eq!(hull.to_image(2).dimensions(), (78, 12));
     }
-}
\ No newline at end of file
+}
@@ -1,114 +1,385 @@
-use std::str::Lines;
-use intcode::IntCode;
+use advent_of_code::intcode::IntCode;
 use std::collections::HashMap;
-use std::io::{stdout, Write};
-use itertools::Itertools;
-use image::{GenericImage, GenericImageView, ImageBuffer, RgbImage};
+use std::env;
+use std::io::{stdout, Stdout, Write};
+use std::time::Duration;
+use advent_of_code::frames::{FrameRecorder, Palette, RecordingOptions};
+use crossterm::{cursor, execute, queue, terminal};
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::style::Print;
 
-type Tile = (i64, i64);
+type Position = (i64, i64);
 
-pub fn main() {
+const TICK: Duration = Duration::from_millis(150);
 
-    let contents = include_str!("../../data/thirteen.data");
-//    draw_tiles(contents);
-    play(contents)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
 }
 
-fn play(contents: &str) {
-
-    let mut intcode = IntCode::initialize(contents, None, true);
-    intcode.program[0] = 2;
-    let mut score = 0;
-    let mut paddle_position = (0, 0);
-    let mut ball_position = (0, 0);
-    let mut tiles: HashMap<Tile, i64> = HashMap::new();
-    let mut step: i32 = 0;
-    while !intcode.is_terminated {
-        step += 1;
-        for i in 0..3 {
-            if paddle_position.0 < ball_position.0 { intcode.set_input(1) }
-            else if paddle_position.0 > ball_position.0 { intcode.set_input(-1) }
-            else { intcode.set_input(0) }
-            intcode.execute();
+impl Tile {
+    fn from(tile_id: i64) -> Tile {
+        match tile_id {
+            0 => Tile::Empty,
+            1 => Tile::Wall,
+            2 => Tile::Block,
+            3 => Tile::Paddle,
+            4 => Tile::Ball,
+            _ => panic!("Invalid tile id {}", tile_id)
         }
+    }
+}
 
-        println!("{:?}", intcode.output_string());
+#[derive(Debug, Default, Clone)]
+pub struct GameState {
+    pub tiles: HashMap<Position, Tile>,
+    pub ball: Option<Position>,
+    pub paddle: Option<Position>,
+    pub score: i64,
+    pub blocks_remaining: usize,
+}
 
-        if !intcode.has_output() {
-            break;
+impl GameState {
+    // The VM draws in (x, y, tile id) triples, except (-1, 0, score) which updates the score.
+    fn update(&mut self, output: &[i64]) {
+        output.chunks(3).for_each(|instruction| {
+            let (x, y, value) = (instruction[0], instruction[1], instruction[2]);
+            if x == -1 && y == 0 {
+                self.score = value;
+            } else {
+                self.draw((x, y), Tile::from(value));
+            }
+        });
+    }
+
+    fn draw(&mut self, position: Position, tile: Tile) {
+        let previous_tile = self.tiles.insert(position, tile);
+        if previous_tile == Some(Tile::Block) { self.blocks_remaining -= 1 }
+        if tile == Tile::Block { self.blocks_remaining += 1 }
+
+        match tile {
+            Tile::Ball => self.ball = Some(position),
+            Tile::Paddle => self.paddle = Some(position),
+            _ => {}
         }
+    }
+
+    pub fn width(&self) -> i64 {
+        self.tiles.keys().map(|x| x.0).max().map_or(0, |x| x + 1)
+    }
 
-        let output = intcode.take_output();
+    pub fn height(&self) -> i64 {
+        self.tiles.keys().map(|x| x.1).max().map_or(0, |x| x + 1)
+    }
+
+    pub fn tile(&self, position: Position) -> Tile {
+        self.tiles.get(&position).cloned().unwrap_or(Tile::Empty)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameEvent {
+    Running,
+    GameOver { score: i64, blocks_remaining: usize },
+}
+
+pub struct Arcade {
+    intcode: IntCode,
+    state: GameState,
+}
+
+impl Arcade {
+    // Boots the cabinet and draws the first screen. Without free play the program
+    // only draws the board and halts, so the game is over straight away.
+    pub fn new(program: &str, free_play: bool) -> Arcade {
+        let mut intcode = IntCode::initialize(program, None, false);
+        if free_play { intcode.program[0] = 2 }
+        intcode.pause_when_waiting_for_input();
+
+        let mut arcade = Arcade { intcode, state: GameState::default() };
+        arcade.run_until_joystick_is_needed();
+        arcade
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
 
-        if output[0] == -1 && output[1] == 0 {
-            println!("score {}", score);
-            score = output[2];
+    pub fn is_game_over(&self) -> bool {
+        self.intcode.is_terminated
+    }
+
+    pub fn event(&self) -> GameEvent {
+        if self.is_game_over() {
+            GameEvent::GameOver { score: self.state.score, blocks_remaining: self.state.blocks_remaining }
         } else {
-            tiles.insert((output[0], output[1]), output[2]);
-        }
-
-        if output[2] == 3 {
-            paddle_position = (output[0], output[1]);
-
-        } else if output[2] == 4 {
-            ball_position = (output[0], output[1]);
-        }
-
-        let mut img = ImageBuffer::from_fn(44 * 7, 25 * 7, |x, y| {
-            let tile_type_optional = tiles.get(&((x/7) as i64,(y/7) as i64));
-            if tile_type_optional.is_none() { return image::Rgb([0,0,0]) }
-            let tile_type = tile_type_optional.unwrap();
-            let red = [220,20,60];
-            let yellow = [255,215,0];
-            let aqua = [0,255,255];
-            let green = [50,205,50];
-            if *tile_type == 3 {
-                image::Rgb(aqua)
-            } else if *tile_type == 4 {
-                image::Rgb(red)
-            } else if *tile_type == 1 {
-                image::Rgb(yellow)
-            } else if *tile_type == 2 {
-                image::Rgb(green)
-            } else { image::Rgb([0,0,0]) }
-        });
+            GameEvent::Running
+        }
+    }
+
+    // Tilts the joystick left (-1), keeps it neutral (0) or tilts it right (1) for one tick.
+    pub fn step(&mut self, joystick: i64) -> GameEvent {
+        if !self.is_game_over() {
+            self.intcode.set_input(joystick.signum());
+            self.run_until_joystick_is_needed();
+        }
+        self.event()
+    }
+
+    fn run_until_joystick_is_needed(&mut self) {
+        while !self.intcode.is_terminated && !self.intcode.is_waiting_for_input() {
+            self.intcode.execute();
+            self.state.update(&self.intcode.take_output());
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    Joystick(i64),
+    TogglePause,
+    Quit,
+}
+
+pub trait Controller {
+    fn next_action(&mut self, state: &GameState) -> Action;
+}
+
+pub struct PaddleFollowsBall;
+
+impl Controller for PaddleFollowsBall {
+    fn next_action(&mut self, state: &GameState) -> Action {
+        match (state.ball, state.paddle) {
+            (Some(ball), Some(paddle)) => Action::Joystick((ball.0 - paddle.0).signum()),
+            _ => Action::Joystick(0)
+        }
+    }
+}
+
+pub struct Keyboard;
+
+impl Controller for Keyboard {
+    // Waits one tick for a key, so the ball keeps moving when nothing is pressed.
+    fn next_action(&mut self, _: &GameState) -> Action {
+        if !event::poll(TICK).unwrap() {
+            return Action::Joystick(0);
+        }
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Left, .. }) => Action::Joystick(-1),
+            Event::Key(KeyEvent { code: KeyCode::Right, .. }) => Action::Joystick(1),
+            Event::Key(KeyEvent { code: KeyCode::Char('p'), .. })
+            | Event::Key(KeyEvent { code: KeyCode::Char(' '), .. }) => Action::TogglePause,
+            Event::Key(KeyEvent { code: KeyCode::Char('q'), .. })
+            | Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => Action::Quit,
+            _ => Action::Joystick(0),
+        }
+    }
+}
+
+pub trait Display {
+    fn show(&mut self, state: &GameState, paused: bool);
+}
 
-        img.save(format!("/tmp/out/part13_{}.png", step)).unwrap();
+pub struct NoDisplay;
 
+impl Display for NoDisplay {
+    fn show(&mut self, _: &GameState, _: bool) {}
+}
+
+pub struct TerminalDisplay {
+    stdout: Stdout,
+}
 
+impl TerminalDisplay {
+    fn open() -> TerminalDisplay {
+        let mut stdout = stdout();
+        terminal::enable_raw_mode().unwrap();
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide).unwrap();
+        TerminalDisplay { stdout }
     }
 
+    fn render(state: &GameState) -> Vec<String> {
+        let mut lines = vec![format!("Score {}  Blocks {}", state.score, state.blocks_remaining)];
+        for y in 0..state.height() {
+            lines.push((0..state.width())
+                .map(|x| match state.tile((x, y)) {
+                    Tile::Wall => '█',
+                    Tile::Block => '#',
+                    Tile::Paddle => '=',
+                    Tile::Ball => 'o',
+                    Tile::Empty => ' '
+                })
+                .collect());
+        }
+        lines
+    }
+}
 
-    println!("Score {}", score);
-    println!("Score {}", score);
-    println!("\r");
+impl Display for TerminalDisplay {
+    fn show(&mut self, state: &GameState, paused: bool) {
+        queue!(self.stdout, cursor::MoveTo(0, 0)).unwrap();
+        for line in TerminalDisplay::render(state) {
+            queue!(self.stdout, Print(line), terminal::Clear(terminal::ClearType::UntilNewLine), Print("\r\n")).unwrap();
+        }
+        let status = if paused { "PAUSED - p to resume, q to quit" } else { "<- -> to move, p to pause, q to quit" };
+        queue!(self.stdout, Print(status), terminal::Clear(terminal::ClearType::UntilNewLine)).unwrap();
+        self.stdout.flush().unwrap();
+    }
+}
 
+impl Drop for TerminalDisplay {
+    fn drop(&mut self) {
+        execute!(self.stdout, cursor::Show, terminal::LeaveAlternateScreen).unwrap();
+        terminal::disable_raw_mode().unwrap();
+    }
+}
 
+pub fn main() {
+    let contents = include_str!("../../data/thirteen.data");
+    let mode = env::args().nth(1).unwrap_or_default();
 
+    match mode.as_str() {
+        "play" => report(play(contents, &mut Keyboard, &mut TerminalDisplay::open())),
+        "watch" => report(play(contents, &mut PaddleFollowsBall, &mut TerminalDisplay::open())),
+        "frames" => save_frames(contents, &RecordingOptions::from_args(env::args().skip(2))),
+        _ => {
+            println!("Blocks on screen {}", draw_tiles(contents));
+            report(play(contents, &mut PaddleFollowsBall, &mut NoDisplay))
+        }
+    }
+}
 
-    println!("\r");
-    println!("Score 1");
-    println!("Score 1");
+fn report(event: Option<GameEvent>) {
+    match event {
+        Some(GameEvent::GameOver { score, blocks_remaining: 0 }) => println!("Won! Score {}", score),
+        Some(GameEvent::GameOver { score, blocks_remaining }) =>
+            println!("Game over with {} blocks left. Score {}", blocks_remaining, score),
+        _ => println!("Quit before the game was over"),
+    }
 }
 
+// Plays a game with free play enabled. Returns the game over event, or None if the controller quit.
+fn play(contents: &str, controller: &mut dyn Controller, display: &mut dyn Display) -> Option<GameEvent> {
+    let mut arcade = Arcade::new(contents, true);
+    let mut paused = false;
 
-fn draw_tiles(contents: &str) {
-    let mut intcode = IntCode::initialize(contents, None, true);
-    let mut tiles: HashMap<Tile, i64> = HashMap::new();
     loop {
-        for i in 0..3 {
-            intcode.execute();
+        display.show(arcade.state(), paused);
+        if arcade.is_game_over() {
+            return Some(arcade.event());
         }
 
-        if !intcode.has_output() {
-            break;
+        match controller.next_action(arcade.state()) {
+            Action::Joystick(_) if paused => {}
+            Action::Joystick(direction) => { arcade.step(direction); }
+            Action::TogglePause => paused = !paused,
+            Action::Quit => return None,
         }
-        let output = intcode.take_output();
-        tiles.insert((output[0], output[1]), output[2]);
     }
-    println!("{:?}", tiles.values()
-        .filter(|&&tile_value| tile_value == 2)
-        .map(|x| *x)
-        .collect::<Vec<i64>>().len());
 }
 
+fn save_frames(contents: &str, options: &RecordingOptions) {
+    let palette = Palette::new([0, 0, 0])
+        .with(Tile::Paddle, [0, 255, 255])
+        .with(Tile::Ball, [220, 20, 60])
+        .with(Tile::Wall, [255, 215, 0])
+        .with(Tile::Block, [50, 205, 50]);
+    let mut recorder = FrameRecorder::with_options(palette, options);
+    let mut arcade = Arcade::new(contents, true);
+    recorder.record(&arcade.state().tiles);
+
+    while !arcade.is_game_over() {
+        if let Action::Joystick(direction) = PaddleFollowsBall.next_action(arcade.state()) {
+            arcade.step(direction);
+        }
+        recorder.record(&arcade.state().tiles);
+    }
+
+    let paths = recorder.save(options, "part13").unwrap();
+    println!("Score {}. Saved {} frames to {:?}", arcade.state().score, recorder.len(), paths[0]);
+}
+
+fn draw_tiles(contents: &str) -> usize {
+    Arcade::new(contents, false).state().blocks_remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{play, draw_tiles, Action, Arcade, Controller, GameEvent, GameState, NoDisplay, PaddleFollowsBall, Tile};
+
+    struct QuitAfter(usize);
+
+    impl Controller for QuitAfter {
+        fn next_action(&mut self, _: &GameState) -> Action {
+            if self.0 == 0 { return Action::Quit }
+            self.0 -= 1;
+            Action::Joystick(0)
+        }
+    }
+
+    struct PauseFirst(PaddleFollowsBall, usize);
+
+    impl Controller for PauseFirst {
+        fn next_action(&mut self, state: &GameState) -> Action {
+            self.1 += 1;
+            if self.1 <= 2 { Action::TogglePause } else { self.0.next_action(state) }
+        }
+    }
+
+    #[test]
+    fn reads_tiles_and_score_from_output() {
+        let mut state = GameState::default();
+        state.update(&[1, 2, 3, 6, 5, 4, 7, 7, 2, 8, 7, 2, -1, 0, 12345]);
+        assert_eq!(state.paddle, Some((1, 2)));
+        assert_eq!(state.ball, Some((6, 5)));
+        assert_eq!(state.score, 12345);
+        assert_eq!(state.blocks_remaining, 2);
+        assert_eq!(state.tile((7, 7)), Tile::Block);
+
+        state.update(&[7, 7, 0, -1, 0, 12346]);
+        assert_eq!(state.blocks_remaining, 1);
+        assert_eq!(state.tile((7, 7)), Tile::Empty);
+        assert_eq!(state.score, 12346);
+    }
+
+    #[test]
+    fn counts_blocks_on_the_start_screen() {
+        let contents = include_str!("../../data/thirteen.data");
+        let arcade = Arcade::new(contents, false);
+        assert!(arcade.is_game_over());
+        assert_eq!(draw_tiles(contents), 412);
+    }
+
+    #[test]
+    fn ai_controller_clears_the_board() {
+        let contents = include_str!("../../data/thirteen.data");
+        let event = play(contents, &mut PaddleFollowsBall, &mut NoDisplay);
+        assert_eq!(event, Some(GameEvent::GameOver { score: 20940, blocks_remaining: 0 }));
+        assert_eq!(play(contents, &mut PauseFirst(PaddleFollowsBall, 0), &mut NoDisplay), event);
+    }
+
+    #[test]
+    fn losing_the_ball_ends_the_game() {
+        let contents = include_str!("../../data/thirteen.data");
+        let mut arcade = Arcade::new(contents, true);
+        let mut event = GameEvent::Running;
+        while event == GameEvent::Running {
+            event = arcade.step(0);
+        }
+        match event {
+            GameEvent::GameOver { blocks_remaining, .. } => assert!(blocks_remaining > 0),
+            GameEvent::Running => unreachable!()
+        }
+        assert_eq!(arcade.step(1), event);
+    }
+
+    #[test]
+    fn quitting_ends_the_game_without_a_score() {
+        let contents = include_str!("../../data/thirteen.data");
+        assert_eq!(play(contents, &mut QuitAfter(2), &mut NoDisplay), None);
+    }
+}
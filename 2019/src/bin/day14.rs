@@ -1,182 +1,61 @@
-use std::str::Lines;
-use intcode::IntCode;
-use regex::Regex;
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
-use std::process::exit;
-use math::round::ceil;
-use num_traits::real::Real;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use advent_of_code::nanofactory::Nanofactory;
 
-static TRACE: bool = true;
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct Chemical {
-    name: String,
-    quantity: i64,
-}
-
-impl Chemical {
-    fn from(string: &str) -> Chemical {
-        let matches = Regex::new(r"(\d+) ([A-Z]+)")
-            .unwrap()
-            .captures(string)
-            .unwrap();
-
-        let quantity = matches.get(1)
-            .unwrap()
-            .as_str()
-            .trim()
-            .parse::<i64>()
-            .unwrap();
-
-        let name = matches.get(2)
-            .unwrap()
-            .as_str()
-            .to_string();
-
-        Chemical { name, quantity }
-    }
-}
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct Reaction {
-    inputs: Vec<Chemical>,
-    output: Chemical,
-}
-
-fn read_input(contents: &str) -> Vec<Reaction> {
-    contents.lines()
-        .into_iter()
-        .map(|line| {
-            let split = line.split("=>")
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>();
-            let inputs = split[0].split(",")
-                .map(|s| s.to_string())
-                .map(|s| Chemical::from(&s))
-                .collect::<Vec<Chemical>>();
-            let output = Chemical::from(&split[1]);
-            Reaction { inputs, output }
-        }).collect::<Vec<Reaction>>()
+fn read_input(contents: &str) -> Nanofactory {
+    Nanofactory::parse(contents).unwrap_or_else(|error| panic!("Reactions are wrong: {}", error))
 }
 
 fn get_ore_requirement(
-    chemical_name: String,
+    chemical_name: &str,
     units_required: i64,
-    reactions: &Vec<Reaction>,
-    mut left_over: &mut HashMap<String, i64>
+    factory: &Nanofactory,
+    left_over: &mut HashMap<String, u64>
 ) -> i64 {
-
-    let reaction = &reactions.iter()
-        .find(|r| r.output.name.eq(&chemical_name))
-        .unwrap();
-
-    let existing_leftover = left_over.get(chemical_name.as_str());
-    let mut units_required_updated = units_required;
-
-    if existing_leftover.is_some() {
-        let leftover_value = existing_leftover.unwrap();
-        units_required_updated = units_required - leftover_value;
-
-        if units_required_updated <= 0 {
-            left_over.insert(chemical_name, leftover_value - units_required);
-            return 0;
-        }
-    }
-
-    let multiplier = (units_required_updated as f32 / reaction.output.quantity as f32).ceil() as i64;
-    left_over.insert(chemical_name, (reaction.output.quantity * multiplier) - units_required_updated);
-
-    let ores_required = reaction.inputs.iter()
-        .map(|input| {
-            if input.name == String::from("ORE") {
-                input.quantity * multiplier
-            } else {
-                return get_ore_requirement(
-                    String::from(&input.name),
-                    input.quantity * multiplier,
-                    &reactions,
-                    &mut left_over
-                );
-            }
-        }).sum();
-
-    ores_required
+    factory.ore_required_from(chemical_name, units_required as u64, left_over)
+        .unwrap_or_else(|error| panic!("{}", error)) as i64
 }
 
-fn produce_from_trillion_units_of_ore(reactions: &Vec<Reaction>) -> i64 {
+const ONE_TRILLION: u64 = 1_000_000_000_000;
 
-    let available_ore = 1000000000000;
-    let mut left = 0;
-    let mut right = available_ore;
-    let mut mid = available_ore / 2;
-    let mut visited_points: HashSet<i64> = HashSet::new();
-
-    loop {
-        let ore_used = get_ore_requirement(String::from("FUEL"), mid, &reactions, &mut HashMap::new());
-        if ore_used < available_ore && visited_points.contains(&mid) {
-            return mid;
-        }
-        visited_points.insert(mid);
-
-        if ore_used < available_ore {
-            //go right
-            left = mid;
-            let new_mid = ((right - left) / 2) as f64;
-            mid += new_mid.ceil() as i64
-        } else {
-            //go left
-            right = mid;
-            let new_mid = ((right - left) / 2) as f64;
-            mid -= new_mid.ceil() as i64
-        }
-    }
-
-    unreachable!()
+fn produce_from_trillion_units_of_ore(factory: &Nanofactory) -> i64 {
+    factory.max_producible("FUEL", ONE_TRILLION).unwrap_or_else(|error| panic!("{}", error)) as i64
 }
 
-
-fn produce_from_trillion_units_of_ore_alternate(reactions: &Vec<Reaction>) -> i64 {
+pub fn main() {
+    let contents = include_str!("../../data/fourteen.data");
+    let factory = read_input(contents);
     let leftover = &mut HashMap::new();
-    let mut stepwise_ores_used_until_complete_cycle = vec![];
-    let one_trillion = 1000000000000;
-    let mut cycles_complete_with_no_leftovers = false;
-    let mut total_ores_used = 0;
-    let mut total_fuel_produced = 0;
-    loop {
-        let ores_used_this_step = get_ore_requirement(String::from("FUEL"), 1, &reactions, leftover);
-
-        if total_ores_used + ores_used_this_step > one_trillion { break }
-        total_ores_used += ores_used_this_step;
-        total_fuel_produced += 1;
-
-        if leftover.values().find(|&x| *x != 0).is_none()
-            && !cycles_complete_with_no_leftovers {
-            let multiplier = one_trillion / total_ores_used;
-            total_ores_used *= multiplier;
-            total_fuel_produced *= multiplier;
-            cycles_complete_with_no_leftovers = true;
+    println!("Ore requirement for 1 unit of fuel: {:?}", get_ore_requirement("FUEL", 1, &factory, leftover));
+    println!("Fuel generated with 1 trillion units of ore: {:?}", produce_from_trillion_units_of_ore(&factory));
+
+    // `plan [fuel]` prints the bill of materials, `json [path]` saves it for one FUEL and
+    // `max <ore> [chemical]` finds how much can be made from another budget.
+    match env::args().nth(1).as_deref() {
+        Some("max") => {
+            let ore = env::args().nth(2).expect("Missing ORE budget").parse().unwrap();
+            let chemical = env::args().nth(3).unwrap_or_else(|| String::from("FUEL"));
+            println!("{} {} from {} ORE", factory.max_producible(&chemical, ore).unwrap(), chemical, ore);
         }
-
-        if !cycles_complete_with_no_leftovers {
-            stepwise_ores_used_until_complete_cycle.push(total_ores_used);
+        Some("plan") => {
+            let fuel = env::args().nth(2).map(|x| x.parse().unwrap()).unwrap_or(1);
+            println!("{}", factory.plan("FUEL", fuel).unwrap());
+        }
+        Some("json") => {
+            let path = env::args().nth(2).map(PathBuf::from).unwrap_or_else(|| {
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/out/part14.json")
+            });
+            factory.plan("FUEL", 1).unwrap().save_json(&path).unwrap();
+            println!("Saved production plan to {:?}", path);
         }
+        _ => {}
     }
-
-    total_fuel_produced
-}
-
-pub fn main() {
-    let contents = include_str!("../../data/fourteen.data");
-    let reactions = read_input(contents);
-    let leftover = &mut HashMap::new();
-    println!("Ore requirement for 1 unit of fuel: {:?}", get_ore_requirement(String::from("FUEL"), 1, &reactions, leftover));
-    println!("Fuel generated with 1 trillion units of ore: {:?}", produce_from_trillion_units_of_ore(&reactions));
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Reaction, Chemical, read_input, get_ore_requirement, produce_from_trillion_units_of_ore, produce_from_trillion_units_of_ore_alternate};
+    use crate::{read_input, get_ore_requirement, produce_from_trillion_units_of_ore};
     use std::collections::HashMap;
 
     #[test]
@@ -237,9 +116,7 @@ mod tests {
 
     #[test]
     fn gets_fuel_produced_from_trillion_ore () {
-
-        //ALTERNATE SOLUTION
-        assert_eq!(setup_and_get_fuel_production_from_trillion_ores_alternate(
+        assert_eq!(setup_and_get_fuel_production_from_trillion_ores(
             "157 ORE => 5 NZVS
             165 ORE => 6 DCFZ
             44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
@@ -285,18 +162,13 @@ mod tests {
     }
 
     fn setup_and_get_fuel_requirement(contents: &str) -> i64 {
-        let reactions = read_input(contents);
-        get_ore_requirement(String::from("FUEL"), 1, &reactions, &mut HashMap::new())
+        let factory = read_input(contents);
+        get_ore_requirement("FUEL", 1, &factory, &mut HashMap::new())
     }
 
     fn setup_and_get_fuel_production_from_trillion_ores(contents: &str) -> i64 {
-        let reactions = read_input(contents);
-        produce_from_trillion_units_of_ore(&reactions)
-    }
-
-    fn setup_and_get_fuel_production_from_trillion_ores_alternate(contents: &str) -> i64 {
-        let reactions = read_input(contents);
-        produce_from_trillion_units_of_ore_alternate(&reactions)
+        let factory = read_input(contents);
+        produce_from_trillion_units_of_ore(&factory)
     }
 
 }
\ No newline at end of file
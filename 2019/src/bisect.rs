@@ -0,0 +1,67 @@
+use std::ops::RangeInclusive;
+
+// Largest x in the range for which the predicate holds, given that it holds up to some
+// point and never after. None if it doesn't even hold at the start.
+pub fn last_true<P: FnMut(u64) -> bool>(range: RangeInclusive<u64>, mut predicate: P) -> Option<u64> {
+    let (mut low, mut high) = range.into_inner();
+    if low > high || !predicate(low) { return None; }
+
+    // predicate(low) holds and predicate(high + 1) doesn't, and every step strictly
+    // shrinks high - low, so this takes at most 64 steps.
+    while low < high {
+        let middle = low + (high - low).div_ceil(2);
+        if predicate(middle) { low = middle } else { high = middle - 1 }
+    }
+    Some(low)
+}
+
+// Largest x >= 0 whose cost fits the budget, for a cost that never decreases as x grows.
+// A cost of None counts as over budget, which is how callers report overflow. The upper
+// bound is found by doubling, so there's no need to guess one.
+pub fn max_within_budget<C: Ord, F: FnMut(u64) -> Option<C>>(budget: C, mut cost: F) -> Option<u64> {
+    let mut fits = |x: u64| cost(x).is_some_and(|x| x <= budget);
+    if !fits(0) { return None; }
+
+    let mut low = 0;
+    let mut high = 1;
+    while fits(high) {
+        if high == u64::MAX { return Some(high); }
+        low = high;
+        high = high.saturating_mul(2);
+    }
+    last_true(low..=high - 1, fits)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bisect::{last_true, max_within_budget};
+
+    #[test]
+    fn finds_last_true() {
+        assert_eq!(last_true(0..=100, |x| x * x <= 50), Some(7));
+        assert_eq!(last_true(0..=100, |x| x <= 100), Some(100));
+        assert_eq!(last_true(5..=100, |x| x < 5), None);
+        assert_eq!(last_true(3..=3, |_| true), Some(3));
+        assert_eq!(last_true(0..=u64::MAX, |x| x < u64::MAX - 1), Some(u64::MAX - 2));
+        assert_eq!(last_true(0..=u64::MAX, |_| true), Some(u64::MAX));
+
+        for end in 0..40 {
+            for cut in 0..=end {
+                assert_eq!(last_true(0..=end, |x| x <= cut), Some(cut));
+            }
+        }
+    }
+
+    #[test]
+    fn finds_maximum_within_budget() {
+        assert_eq!(max_within_budget(1_000_000_000_000, |x| Some(x * 3)), Some(333_333_333_333));
+        assert_eq!(max_within_budget(10, |x| Some(x / 4)), Some(43));
+        assert_eq!(max_within_budget(0, |x| Some(x + 1)), None);
+        assert_eq!(max_within_budget(u64::MAX, Some), Some(u64::MAX));
+        assert_eq!(max_within_budget(u64::MAX, |x| x.checked_mul(2)), Some(u64::MAX / 2));
+
+        let mut calls = 0;
+        max_within_budget(1_000_000_000_000, |x| { calls += 1; Some(x) });
+        assert!(calls <= 2 * 64);
+    }
+}
@@ -0,0 +1,90 @@
+// Where a sequence x0, f(x0), f(f(x0)), ... starts repeating: the states from `tail` on
+// come back every `period` steps.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Cycle {
+    pub tail: u64,
+    pub period: u64,
+}
+
+impl Cycle {
+    // Steps until a state is seen for the second time.
+    pub fn first_repeat(&self) -> u64 {
+        self.tail + self.period
+    }
+
+    // The cycle of several sequences stepped together, once all of them are inside their
+    // own cycles.
+    pub fn combine<I: IntoIterator<Item = Cycle>>(cycles: I) -> Cycle {
+        cycles.into_iter().fold(Cycle { tail: 0, period: 1 }, |combined, x| Cycle {
+            tail: combined.tail.max(x.tail),
+            period: num::integer::lcm(combined.period, x.period),
+        })
+    }
+}
+
+// Brent's algorithm, keeping two states at a time. Finding the period compares against
+// a state saved at each power of two, then the tail comes from walking two states the
+// period apart until they meet.
+pub fn brent<T: Clone + PartialEq, F: Fn(&T) -> T>(start: &T, step: F) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut saved = start.clone();
+    let mut current = step(start);
+    while saved != current {
+        if power == period {
+            saved = current.clone();
+            power *= 2;
+            period = 0;
+        }
+        current = step(&current);
+        period += 1;
+    }
+
+    let mut behind = start.clone();
+    let mut ahead = start.clone();
+    (0..period).for_each(|_| ahead = step(&ahead));
+    let mut tail = 0;
+    while behind != ahead {
+        behind = step(&behind);
+        ahead = step(&ahead);
+        tail += 1;
+    }
+    Cycle { tail, period }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cycles::{brent, Cycle};
+
+    #[test]
+    fn finds_tail_and_period() {
+        assert_eq!(brent(&0, |x| (x + 1) % 7), Cycle { tail: 0, period: 7 });
+        assert_eq!(brent(&5, |x| *x), Cycle { tail: 0, period: 1 });
+        assert_eq!(brent(&0, |x| if *x < 10 { x + 1 } else { 4 }), Cycle { tail: 4, period: 7 });
+        assert_eq!(brent(&2u64, |x| x * x % 10_007), brute_force(2, |x| x * x % 10_007));
+
+        for start in 0..50 {
+            let step = |x: &u64| (x * x + 3) % 97;
+            assert_eq!(brent(&start, step), brute_force(start, step));
+        }
+    }
+
+    #[test]
+    fn combines_cycles() {
+        let cycles = vec![Cycle { tail: 0, period: 18 }, Cycle { tail: 3, period: 28 }, Cycle { tail: 1, period: 44 }];
+        assert_eq!(Cycle::combine(cycles), Cycle { tail: 3, period: 2772 });
+        assert_eq!(Cycle::combine(vec![]), Cycle { tail: 0, period: 1 });
+        assert_eq!(Cycle { tail: 4, period: 7 }.first_repeat(), 11);
+    }
+
+    fn brute_force<F: Fn(&u64) -> u64>(start: u64, step: F) -> Cycle {
+        let mut seen = vec![start];
+        loop {
+            let next = step(seen.last().unwrap());
+            if let Some(tail) = seen.iter().position(|x| *x == next) {
+                return Cycle { tail: tail as u64, period: (seen.len() - tail) as u64 };
+            }
+            seen.push(next);
+        }
+    }
+}
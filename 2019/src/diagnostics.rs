@@ -0,0 +1,103 @@
+use crate::intcode::IntCode;
+
+// Runs the TEST diagnostic program for the given system.
+pub fn run(program: &str, system_id: i64) -> IntCode {
+    let mut intcode = IntCode::initialize(program, Some(system_id), false);
+    intcode.execute();
+    intcode
+}
+
+// The diagnostic code is the last output, after a 0 for every check that passed.
+pub fn diagnose(program: &str, system_id: i64) -> (IntCode, i64) {
+    let mut intcode = run(program, system_id);
+
+    let output = intcode.take_output();
+    let (diagnostic_code, test_results) = output.split_last().expect("diagnostic program produced no output");
+    if test_results.iter().any(|&x| x != 0) {
+        panic!("It can't be non-zero: {:?}", test_results)
+    }
+    (intcode, *diagnostic_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{diagnose, run};
+
+    #[test]
+    fn uses_input_mode() {
+        let memory = run(String::from("1002,4,3,4,33").as_str(), 1);
+        assert_eq!(memory.memory_string(), "1002,4,3,4,99");
+    }
+
+    #[test]
+    #[should_panic(expected = "diagnostic program produced no output")]
+    fn fails_without_diagnostic_code() {
+        diagnose("1002,4,3,4,33", 1);
+    }
+
+    #[test]
+    fn returns_output_for_test_with_opcode_8_using_position_mode() {
+        let (_, output) = diagnose(
+            String::from("3,9,8,9,10,9,4,9,99,-1,8").as_str(), 8);
+        assert_eq!(output, 1);
+
+        let (_, output) = diagnose(
+            String::from("3,9,8,9,10,9,4,9,99,-1,8").as_str(), 1);
+        assert_eq!(output, 0);
+    }
+
+    #[test]
+    fn returns_output_for_test_with_opcode_8_using_immediate_mode() {
+        let (_, output) = diagnose(
+            String::from("3,3,1108,-1,8,3,4,3,99").as_str(), 8);
+        assert_eq!(output, 1);
+
+        let (_, output) = diagnose(
+            String::from("3,3,1108,-1,8,3,4,3,99").as_str(), 1);
+        assert_eq!(output, 0);
+    }
+
+    #[test]
+    fn returns_output_for_test_with_opcode_7_using_position_mode() {
+        let (_, output) = diagnose(
+            String::from("3,9,7,9,10,9,4,9,99,-1,8").as_str(), 7);
+        assert_eq!(output, 1);
+
+        let (_, output) = diagnose(
+            String::from("3,9,7,9,10,9,4,9,99,-1,8").as_str(), 8);
+        assert_eq!(output, 0);
+    }
+
+    #[test]
+    fn returns_output_for_test_with_opcode_7_using_immediate_mode() {
+        let (_, output) = diagnose(
+            String::from("3,3,1107,-1,8,3,4,3,99").as_str(), 7);
+        assert_eq!(output, 1);
+
+        let (_, output) = diagnose(
+            String::from("3,3,1107,-1,8,3,4,3,99").as_str(), 8);
+        assert_eq!(output, 0);
+    }
+
+    #[test]
+    fn returns_output_for_test_jumps_using_position_mode() {
+        let (_, output) = diagnose(
+            String::from("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9").as_str(), 7);
+        assert_eq!(output, 1);
+
+        let (_, output) = diagnose(
+            String::from("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9").as_str(), 0);
+        assert_eq!(output, 0);
+    }
+
+    #[test]
+    fn returns_output_for_test_jumps_using_immediate_mode() {
+        let (_, output) = diagnose(
+            String::from("3,3,1105,-1,9,1101,0,0,12,4,12,99,1").as_str(), 7);
+        assert_eq!(output, 1);
+
+        let (_, output) = diagnose(
+            String::from("3,3,1105,-1,9,1101,0,0,12,4,12,99,1").as_str(), 0);
+        assert_eq!(output, 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,257 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use image::{ImageBuffer, ImageResult, Rgb, RgbImage};
+use image::gif::Encoder;
+use num::rational::Ratio;
+
+pub type Color = [u8; 3];
+pub type Cell = (i64, i64);
+
+pub struct Palette<T> {
+    colors: HashMap<T, Color>,
+    background: Color,
+}
+
+impl<T: Eq + Hash> Palette<T> {
+    pub fn new(background: Color) -> Palette<T> {
+        Palette { colors: HashMap::new(), background }
+    }
+
+    pub fn with(mut self, value: T, color: Color) -> Palette<T> {
+        self.colors.insert(value, color);
+        self
+    }
+
+    pub fn color(&self, value: &T) -> Color {
+        *self.colors.get(value).unwrap_or(&self.background)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FrameFormat {
+    Gif,
+    PngSequence,
+}
+
+// Where and how to write frames, read from command line arguments like
+// `--out /tmp/frames --format png --scale 4 --skip 10`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingOptions {
+    pub directory: PathBuf,
+    pub format: FrameFormat,
+    pub scale: u32,
+    pub frame_skip: usize,
+}
+
+impl RecordingOptions {
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> RecordingOptions {
+        let mut options = RecordingOptions {
+            directory: PathBuf::from("out"),
+            format: FrameFormat::Gif,
+            scale: 4,
+            frame_skip: 0,
+        };
+        while let Some(arg) = args.next() {
+            let value = args.next().unwrap_or_else(|| panic!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--out" => options.directory = PathBuf::from(value),
+                "--format" => options.format = match value.as_str() {
+                    "gif" => FrameFormat::Gif,
+                    "png" => FrameFormat::PngSequence,
+                    _ => panic!("Unknown frame format {}, expected gif or png", value)
+                },
+                "--scale" => options.scale = value.parse().unwrap(),
+                "--skip" => options.frame_skip = value.parse().unwrap(),
+                _ => panic!("Unknown option {}", arg)
+            }
+        }
+        options
+    }
+}
+
+// Collects snapshots of a grid while a puzzle runs. Cells that are missing from
+// a snapshot are drawn with the palette background, and every frame is drawn
+// over the bounds of all recorded frames so the animation doesn't jump around.
+pub struct FrameRecorder<T> {
+    palette: Palette<T>,
+    scale: u32,
+    frame_skip: usize,
+    delay: u16,
+    frames_offered: usize,
+    frames: Vec<Vec<(Cell, T)>>,
+}
+
+impl<T: Copy + Eq + Hash> FrameRecorder<T> {
+    pub fn new(palette: Palette<T>) -> FrameRecorder<T> {
+        FrameRecorder { palette, scale: 1, frame_skip: 0, delay: 5, frames_offered: 0, frames: vec![] }
+    }
+
+    pub fn with_options(palette: Palette<T>, options: &RecordingOptions) -> FrameRecorder<T> {
+        FrameRecorder::new(palette)
+            .scale(options.scale)
+            .frame_skip(options.frame_skip)
+    }
+
+    // Size in pixels of one grid cell.
+    pub fn scale(mut self, scale: u32) -> FrameRecorder<T> {
+        self.scale = scale.max(1);
+        self
+    }
+
+    // Number of frames to drop after each recorded one.
+    pub fn frame_skip(mut self, frame_skip: usize) -> FrameRecorder<T> {
+        self.frame_skip = frame_skip;
+        self
+    }
+
+    // Delay between GIF frames, in hundredths of a second.
+    pub fn delay(mut self, delay: u16) -> FrameRecorder<T> {
+        self.delay = delay;
+        self
+    }
+
+    // Takes a snapshot of the grid, e.g. `recorder.record(&tiles)` for a `HashMap<Cell, T>`.
+    pub fn record<I, C, V>(&mut self, cells: I)
+        where I: IntoIterator<Item = (C, V)>, C: Borrow<Cell>, V: Borrow<T> {
+        let is_skipped = !self.frames_offered.is_multiple_of(self.frame_skip + 1);
+        self.frames_offered += 1;
+        if is_skipped { return; }
+
+        self.frames.push(cells.into_iter().map(|(cell, value)| (*cell.borrow(), *value.borrow())).collect());
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn render(&self) -> Vec<RgbImage> {
+        let cells = || self.frames.iter().flat_map(|frame| frame.iter().map(|(cell, _)| *cell));
+        let min_x = cells().map(|cell| cell.0).min().unwrap_or(0);
+        let max_x = cells().map(|cell| cell.0).max().unwrap_or(0);
+        let min_y = cells().map(|cell| cell.1).min().unwrap_or(0);
+        let max_y = cells().map(|cell| cell.1).max().unwrap_or(0);
+        let width = (max_x - min_x + 1) as u32 * self.scale;
+        let height = (max_y - min_y + 1) as u32 * self.scale;
+
+        self.frames.iter().map(|frame| {
+            let mut image: RgbImage = ImageBuffer::from_pixel(width, height, Rgb(self.palette.background));
+            for (cell, value) in frame {
+                let color = Rgb(self.palette.color(value));
+                let left = (cell.0 - min_x) as u32 * self.scale;
+                let top = (cell.1 - min_y) as u32 * self.scale;
+                for x in left..left + self.scale {
+                    for y in top..top + self.scale {
+                        image.put_pixel(x, y, color);
+                    }
+                }
+            }
+            image
+        }).collect()
+    }
+
+    pub fn save(&self, options: &RecordingOptions, name: &str) -> ImageResult<Vec<PathBuf>> {
+        match options.format {
+            FrameFormat::Gif => self.save_gif(&options.directory, name).map(|path| vec![path]),
+            FrameFormat::PngSequence => self.save_png_sequence(&options.directory, name),
+        }
+    }
+
+    // Writes `<directory>/<name>.gif`, creating the directory if needed.
+    pub fn save_gif(&self, directory: &Path, name: &str) -> ImageResult<PathBuf> {
+        fs::create_dir_all(directory)?;
+        let path = directory.join(format!("{}.gif", name));
+        let frames = self.render().into_iter().map(|frame| {
+            let rgba = image::DynamicImage::ImageRgb8(frame).to_rgba();
+            image::Frame::from_parts(rgba, 0, 0, Ratio::from_integer(self.delay))
+        });
+
+        Encoder::new(File::create(&path)?).encode_frames(frames)?;
+        Ok(path)
+    }
+
+    // Writes `<directory>/<name>_00001.png` and onwards, creating the directory if needed.
+    pub fn save_png_sequence(&self, directory: &Path, name: &str) -> ImageResult<Vec<PathBuf>> {
+        fs::create_dir_all(directory)?;
+        self.render().iter().enumerate().map(|(index, frame)| {
+            let path = directory.join(format!("{}_{:05}.png", name, index + 1));
+            frame.save(&path)?;
+            Ok(path)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frames::{FrameFormat, FrameRecorder, Palette, RecordingOptions};
+    use std::collections::HashMap;
+    use std::env;
+    use std::path::PathBuf;
+    use image::Rgb;
+
+    fn recorder() -> FrameRecorder<i64> {
+        FrameRecorder::new(Palette::new([0, 0, 0]).with(1, [255, 255, 255]).with(2, [255, 0, 0]))
+    }
+
+    #[test]
+    fn renders_every_frame_over_the_same_bounds() {
+        let mut recorder = recorder().scale(2);
+        recorder.record(vec![((0, 0), 1)]);
+        recorder.record(vec![((-1, 0), 2), ((1, 2), 1)]);
+
+        let frames = recorder.render();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].dimensions(), (6, 6));
+        assert_eq!(frames[1].dimensions(), (6, 6));
+        assert_eq!(*frames[0].get_pixel(2, 0), Rgb([255, 255, 255]));
+        assert_eq!(*frames[0].get_pixel(0, 0), Rgb([0, 0, 0]));
+        assert_eq!(*frames[1].get_pixel(1, 1), Rgb([255, 0, 0]));
+        assert_eq!(*frames[1].get_pixel(5, 5), Rgb([255, 255, 255]));
+    }
+
+    #[test]
+    fn skips_frames() {
+        let mut recorder = recorder().frame_skip(2);
+        let grid: HashMap<(i64, i64), i64> = vec![((0, 0), 1)].into_iter().collect();
+        for _ in 0..7 {
+            recorder.record(&grid);
+        }
+        assert_eq!(recorder.len(), 3);
+    }
+
+    #[test]
+    fn reads_options_from_arguments() {
+        let args = vec!["--out", "/tmp/x", "--format", "png", "--scale", "3", "--skip", "9"];
+        let options = RecordingOptions::from_args(args.into_iter().map(String::from));
+        assert_eq!(options, RecordingOptions {
+            directory: PathBuf::from("/tmp/x"),
+            format: FrameFormat::PngSequence,
+            scale: 3,
+            frame_skip: 9,
+        });
+    }
+
+    #[test]
+    fn writes_gif_and_png_sequence_to_new_directory() {
+        let directory = env::temp_dir().join(format!("frames_test_{}", std::process::id()));
+        let mut recorder = recorder();
+        let grid: HashMap<(i64, i64), i64> = vec![((0, 0), 1), ((3, 1), 2)].into_iter().collect();
+        recorder.record(&grid);
+        recorder.record(&grid);
+
+        let gif = recorder.save_gif(&directory, "animation").unwrap();
+        let pngs = recorder.save_png_sequence(&directory.join("pngs"), "frame").unwrap();
+
+        assert!(gif.exists());
+        assert_eq!(pngs.iter().map(|x| x.file_name().unwrap().to_str().unwrap()).collect::<Vec<&str>>(),
+                   vec!["frame_00001.png", "frame_00002.png"]);
+        assert_eq!(image::open(&pngs[1]).unwrap().to_rgb().dimensions(), (4, 2));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use crate::intcode::IntCode;
+
+// Runs the program in its own memory, first writing the noun and verb to addresses 1 and 2
+// when given.
+pub fn run(program: &str, noun_and_verb: Option<(i64, i64)>) -> IntCode {
+    let mut intcode = IntCode::initialize(program, None, false);
+    intcode.stop_at_memory_not_available();
+    if let Some((noun, verb)) = noun_and_verb {
+        intcode.program[1] = noun;
+        intcode.program[2] = verb;
+    }
+    intcode.execute();
+    intcode
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gravity_assist::run;
+
+    #[test]
+    fn it_works() {
+        assert_eq!(run(String::from("2,4,3,4,33").as_str(), None).memory_string(), "2,4,3,4,132");
+        assert_eq!(run(String::from("1,0,0,0,99").as_str(), None).memory_string(), "2,0,0,0,99");
+        assert_eq!(run(String::from("2,3,0,3,99").as_str(), None).memory_string(), "2,3,0,6,99");
+        assert_eq!(run(String::from("2,4,4,5,99,0").as_str(), None).memory_string(), "2,4,4,5,99,9801");
+        assert_eq!(run(String::from("1,1,1,4,99,5,6,0,99").as_str(), None).memory_string(), "30,1,1,4,2,5,6,0,99");
+    }
+}
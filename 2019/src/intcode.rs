@@ -12,6 +12,7 @@ pub struct IntCode {
     output: Vec<i64>,
     relative_base: i64,
     should_stop_at_memory_not_available: bool,
+    should_pause_for_input: bool,
     immediate_output_mode: bool,
     pub is_terminated: bool
 }
@@ -29,6 +30,7 @@ impl IntCode {
             output: Vec::new(),
             relative_base: 0,
             should_stop_at_memory_not_available: false,
+            should_pause_for_input: false,
             immediate_output_mode,
             is_terminated: false
         }
@@ -82,11 +84,25 @@ impl IntCode {
         self.program.append(&mut pad_vector);
     }
 
-    pub fn execute(&mut self) {
-        let index_out_of_bounds = self.current_opcode_position >= self.program.len();
-        let continue_iteration: bool = !index_out_of_bounds || !self.should_stop_at_memory_not_available;
+    pub fn stop_at_memory_not_available(&mut self) {
+        self.should_stop_at_memory_not_available = true;
+    }
 
-        while continue_iteration
+    // Makes `execute` return on an input instruction with no input set, instead of panicking,
+    // so the caller can check `is_waiting_for_input` and provide one.
+    pub fn pause_when_waiting_for_input(&mut self) {
+        self.should_pause_for_input = true;
+    }
+
+    fn is_at_end_of_memory(&self) -> bool {
+        self.should_stop_at_memory_not_available
+            && self.current_opcode_position + 1 >= self.program.len()
+    }
+
+    // Runs until the program halts, or until it outputs in immediate output mode, or until it
+    // needs input that isn't there when pausing for input. Only halting sets `is_terminated`.
+    pub fn execute(&mut self) {
+        while !self.is_at_end_of_memory()
             && self.get_memory(self.current_opcode_position) != 99 {
             self.current_instruction = self.get_memory(self.current_opcode_position);
 
@@ -96,7 +112,7 @@ impl IntCode {
             match self.current_instruction % 10 {
                 1 => self.add(),
                 2 => self.multiply(),
-                3 => self.store_input(),
+                3 => if self.input.is_none() && self.should_pause_for_input { return; } else { self.store_input() },
                 4 => if self.store_output() { return; },
                 5 => self.jump_if_true(),
                 6 => self.jump_if_false(),
@@ -133,7 +149,7 @@ impl IntCode {
     fn store_input(&mut self) {
         let output_position = self.get_output_position(1);
 
-        if self.input.is_none() { panic!(dbg!("No input")) }
+        if self.input.is_none() { panic!("No input") }
         if TRACE { println!("Storing {} in {}", self.input.unwrap(), output_position)}
         self.store_memory(output_position, self.input.unwrap());
         self.input = None;
@@ -207,6 +223,12 @@ impl IntCode {
         self.input = Some(input);
     }
 
+    pub fn is_waiting_for_input(&self) -> bool {
+        !self.is_terminated
+            && self.input.is_none()
+            && self.program.get(self.current_opcode_position).is_some_and(|x| x % 10 == 3)
+    }
+
     pub fn has_output(&self) -> bool {
         self.output.len() != 0
     }
@@ -221,7 +243,7 @@ impl IntCode {
         println!("{}", self.program.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","))
     }
 
-    fn memory_string(&self) -> String {
+    pub fn memory_string(&self) -> String {
         format!("{}", self.program.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","))
     }
 
@@ -294,6 +316,28 @@ mod tests {
         assert_eq!(intcode.output_string(), "1125899906842624")
     }
 
+    #[test]
+    fn pauses_for_missing_input_when_asked() {
+        let mut intcode = IntCode::initialize("3,7,4,7,3,7,99,0", Some(5), false);
+        intcode.pause_when_waiting_for_input();
+        intcode.execute();
+        assert!(!intcode.is_terminated);
+        assert!(intcode.is_waiting_for_input());
+        assert_eq!(intcode.take_output(), vec![5]);
+
+        intcode.set_input(6);
+        intcode.execute();
+        assert!(intcode.is_terminated);
+        assert!(!intcode.is_waiting_for_input());
+        assert_eq!(intcode.memory_string(), "3,7,4,7,3,7,99,6");
+    }
+
+    #[test]
+    #[should_panic(expected = "No input")]
+    fn panics_on_missing_input_by_default() {
+        intcode_execute("3,7,4,7,3,7,99,0", Some(5));
+    }
+
     pub fn intcode_output(program: &str, input: i64) -> i64 {
         let intcode = intcode_execute(program, Some(input));
         intcode.output[0]
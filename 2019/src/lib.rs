@@ -0,0 +1,18 @@
+pub mod intcode;
+pub mod frames;
+pub mod ocr;
+pub mod sif;
+pub mod asteroids;
+pub mod wires;
+pub mod passwords;
+pub mod orbits;
+pub mod nanofactory;
+pub mod bisect;
+pub mod nbody;
+pub mod cycles;
+pub mod search;
+pub mod maze;
+pub mod oxygen;
+pub mod gravity_assist;
+pub mod diagnostics;
+pub mod amplifiers;
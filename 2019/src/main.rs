@@ -1 +1,3 @@
-pub mod intcode;
\ No newline at end of file
+pub fn main() {
+    println!("Pick a day to run, e.g. cargo run --bin day09");
+}
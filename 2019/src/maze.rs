@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use std::{fmt, fs, io};
+use std::path::Path;
+use crate::intcode::IntCode;
+use crate::search::{bfs, Graph};
+use crate::maze::PositionStatus::{Wall, Home, Visited, LocationOfOxygenSystem, NotVisited};
+
+pub type Position = (i64, i64);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    // The order the explorer tries them in.
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    // The droid's movement command.
+    pub fn command(self) -> i64 {
+        match self {
+            Direction::North => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+            Direction::East => 4,
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        }
+    }
+
+    // North is up, towards smaller y.
+    pub fn step(self, position: Position) -> Position {
+        match self {
+            Direction::North => (position.0, position.1 - 1),
+            Direction::South => (position.0, position.1 + 1),
+            Direction::West => (position.0 - 1, position.1),
+            Direction::East => (position.0 + 1, position.1),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self {
+            Direction::North => "N",
+            Direction::South => "S",
+            Direction::West => "W",
+            Direction::East => "E",
+        };
+        write!(f, "{}", letter)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PositionStatus {
+    Wall,
+    Home,
+    Visited,
+    LocationOfOxygenSystem,
+    NotVisited,
+}
+
+impl PositionStatus {
+    // Whether the droid can stand there.
+    pub fn is_open(self) -> bool {
+        [Visited, Home, LocationOfOxygenSystem].contains(&self)
+    }
+
+    pub fn glyph(self) -> char {
+        match self {
+            Visited => '.',
+            Home => 'X',
+            Wall => '█',
+            LocationOfOxygenSystem => 'o',
+            NotVisited => '+',
+        }
+    }
+}
+
+// A position the droid has seen, with the move that first reached it when it's open.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MazeBlock {
+    pub position: Position,
+    pub status: PositionStatus,
+    pub last_visited_from: Option<Position>,
+    pub last_visited_from_direction: Option<Direction>,
+}
+
+impl MazeBlock {
+    pub fn from(position: Position,
+                status: PositionStatus,
+                last_visited_from: Option<Position>,
+                last_visited_from_direction: Option<Direction>) -> MazeBlock {
+        MazeBlock {
+            position,
+            status,
+            last_visited_from,
+            last_visited_from_direction,
+        }
+    }
+}
+
+// What the droid reports after being told to move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Reply {
+    HitWall,
+    Moved,
+    FoundOxygenSystem,
+}
+
+pub trait Droid {
+    fn send(&mut self, direction: Direction) -> Reply;
+}
+
+impl Droid for IntCode {
+    fn send(&mut self, direction: Direction) -> Reply {
+        self.set_input(direction.command());
+        self.execute();
+        match self.take_output()[..] {
+            [0] => Reply::HitWall,
+            [1] => Reply::Moved,
+            [2] => Reply::FoundOxygenSystem,
+            ref output => panic!("Unexpected droid output {:?}", output),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MazeError {
+    // Lines and columns are counted from 1.
+    InvalidGlyph { line: usize, column: usize, glyph: char },
+    // A second home or oxygen system.
+    DuplicateGlyph { line: usize, column: usize, glyph: char },
+    MissingHome,
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::InvalidGlyph { line, column, glyph } =>
+                write!(f, "Unknown glyph {:?} on line {}, column {}", glyph, line, column),
+            MazeError::DuplicateGlyph { line, column, glyph } =>
+                write!(f, "Second {:?} on line {}, column {}", glyph, line, column),
+            MazeError::MissingHome => write!(f, "The maze has no home X"),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+// Everything seen so far. Positions that aren't in the map are unknown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Maze {
+    blocks: HashMap<Position, MazeBlock>,
+}
+
+impl Maze {
+    pub fn new(blocks: HashMap<Position, MazeBlock>) -> Maze {
+        Maze { blocks }
+    }
+
+    // Reads a maze drawn like `render` does, with or without the `|` around each row. The
+    // home ends up at (0, 0) wherever it's drawn. Open blocks don't know how the droid
+    // reached them.
+    pub fn parse(text: &str) -> Result<Maze, MazeError> {
+        let mut blocks = vec![];
+        let mut home = None;
+        let mut oxygen_system = None;
+        for (y, line) in text.lines().enumerate() {
+            let row = line.strip_prefix('|').and_then(|x| x.strip_suffix('|')).unwrap_or(line);
+            for (x, glyph) in row.chars().enumerate() {
+                let status = match glyph {
+                    '.' => Visited,
+                    'X' => Home,
+                    '█' => Wall,
+                    'o' => LocationOfOxygenSystem,
+                    '+' => continue,
+                    _ => return Err(MazeError::InvalidGlyph { line: y + 1, column: x + 1, glyph }),
+                };
+                let position = (x as i64, y as i64);
+                let seen = match status {
+                    Home => home.replace(position),
+                    LocationOfOxygenSystem => oxygen_system.replace(position),
+                    _ => None,
+                };
+                if seen.is_some() {
+                    return Err(MazeError::DuplicateGlyph { line: y + 1, column: x + 1, glyph });
+                }
+                blocks.push((position, status));
+            }
+        }
+
+        let (home_x, home_y) = home.ok_or(MazeError::MissingHome)?;
+        Ok(Maze::new(blocks.into_iter()
+            .map(|((x, y), status)| {
+                let position = (x - home_x, y - home_y);
+                (position, MazeBlock::from(position, status, None, None))
+            })
+            .collect()))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+
+    pub fn blocks(&self) -> &HashMap<Position, MazeBlock> {
+        &self.blocks
+    }
+
+    pub fn status(&self, position: Position) -> PositionStatus {
+        self.blocks.get(&position).map_or(NotVisited, |x| x.status)
+    }
+
+    pub fn home(&self) -> Option<Position> {
+        self.find(Home)
+    }
+
+    pub fn oxygen_system(&self) -> Option<Position> {
+        self.find(LocationOfOxygenSystem)
+    }
+
+    fn find(&self, status: PositionStatus) -> Option<Position> {
+        self.blocks.values().find(|x| x.status == status).map(|x| x.position)
+    }
+
+    // Smallest and largest corners, both included.
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        let min_x = self.blocks.keys().map(|x| x.0).min()?;
+        let max_x = self.blocks.keys().map(|x| x.0).max()?;
+        let min_y = self.blocks.keys().map(|x| x.1).min()?;
+        let max_y = self.blocks.keys().map(|x| x.1).max()?;
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+
+    // Unknown positions next to an open one, which the droid could still try, by row.
+    pub fn unknown(&self) -> Vec<Position> {
+        let mut unknown: Vec<Position> = self.blocks.values()
+            .filter(|x| x.status.is_open())
+            .flat_map(|x| Direction::ALL.iter().map(move |direction| direction.step(x.position)))
+            .filter(|x| !self.blocks.contains_key(x))
+            .collect();
+        unknown.sort_by_key(|x| (x.1, x.0));
+        unknown.dedup();
+        unknown
+    }
+
+    pub fn is_fully_explored(&self) -> bool {
+        self.unknown().is_empty()
+    }
+
+    // Commands taking the droid from one open position to another in as few moves as possible.
+    pub fn route(&self, from: Position, to: Position) -> Option<Vec<Direction>> {
+        let path = bfs(self, from).path_to(&to)?;
+        Some(path.windows(2)
+            .map(|x| *Direction::ALL.iter().find(|direction| direction.step(x[0]) == x[1]).unwrap())
+            .collect())
+    }
+
+    pub fn route_to_oxygen_system(&self) -> Option<Vec<Direction>> {
+        self.route(self.home()?, self.oxygen_system()?)
+    }
+
+    // One line per row between the bounds, with unknown positions inside them as `+`.
+    pub fn render(&self) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = match self.bounds() { Some(x) => x, None => return String::new() };
+        (min_y..=max_y)
+            .map(|y| {
+                let row: String = (min_x..=max_x).map(|x| self.status((x, y)).glyph()).collect();
+                format!("|{}|\n", row)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Maze {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+// Open positions lead to the open positions next to them.
+impl Graph for Maze {
+    type Node = Position;
+
+    fn neighbors(&self, position: &Position) -> Vec<Position> {
+        Direction::ALL.iter()
+            .map(|x| x.step(*position))
+            .filter(|x| self.status(*x).is_open())
+            .collect()
+    }
+}
+
+// Walks the droid depth first from home at (0, 0), trying every unknown neighbour and
+// retracing its steps once there are none left.
+pub struct Explorer<D: Droid> {
+    droid: D,
+    maze: Maze,
+    position: Position,
+    trail: Vec<Direction>,
+}
+
+impl<D: Droid> Explorer<D> {
+    pub fn new(droid: D) -> Explorer<D> {
+        let home = MazeBlock::from((0, 0), Home, None, None);
+        let maze = Maze::new(vec![((0, 0), home)].into_iter().collect());
+        Explorer { droid, maze, position: (0, 0), trail: vec![] }
+    }
+
+    pub fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    // Sends one command, or returns false when everything reachable has been seen.
+    pub fn step(&mut self) -> bool {
+        let unknown = Direction::ALL.iter()
+            .find(|x| !self.maze.blocks.contains_key(&x.step(self.position)));
+        let (direction, going_backwards) = match (unknown, self.trail.last()) {
+            (Some(direction), _) => (*direction, false),
+            (None, Some(direction)) => (direction.opposite(), true),
+            (None, None) => return false,
+        };
+
+        let next_position = direction.step(self.position);
+        let status = match self.droid.send(direction) {
+            Reply::HitWall => {
+                self.maze.blocks.insert(next_position, MazeBlock::from(next_position, Wall, None, None));
+                return true;
+            }
+            Reply::Moved => Visited,
+            Reply::FoundOxygenSystem => LocationOfOxygenSystem,
+        };
+        if going_backwards {
+            self.trail.pop();
+        } else {
+            let block = MazeBlock::from(next_position, status, Some(self.position), Some(direction));
+            self.maze.blocks.insert(next_position, block);
+            self.trail.push(direction);
+        }
+        self.position = next_position;
+        true
+    }
+
+    pub fn explore(mut self) -> Maze {
+        while self.step() {}
+        self.maze
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::{Direction, Droid, Explorer, Maze, MazeError, Position, PositionStatus, Reply};
+
+    // A droid walking a drawn map, with home at (0, 0) wherever the X is.
+    struct MapDroid {
+        rows: Vec<Vec<char>>,
+        home: Position,
+        position: Position,
+    }
+
+    impl MapDroid {
+        fn new(rows: &[&str]) -> MapDroid {
+            let rows: Vec<Vec<char>> = rows.iter().map(|x| x.chars().collect()).collect();
+            let y = rows.iter().position(|x| x.contains(&'X')).unwrap();
+            let x = rows[y].iter().position(|x| *x == 'X').unwrap();
+            MapDroid { rows, home: (x as i64, y as i64), position: (0, 0) }
+        }
+    }
+
+    impl Droid for MapDroid {
+        fn send(&mut self, direction: Direction) -> Reply {
+            let next = direction.step(self.position);
+            let (x, y) = (next.0 + self.home.0, next.1 + self.home.1);
+            match self.rows[y as usize][x as usize] {
+                '#' => Reply::HitWall,
+                cell => {
+                    self.position = next;
+                    if cell == 'o' { Reply::FoundOxygenSystem } else { Reply::Moved }
+                }
+            }
+        }
+    }
+
+    const MAP: [&str; 6] = [
+        "#######",
+        "#.....#",
+        "#.###.#",
+        "#X#o..#",
+        "#...#.#",
+        "#######",
+    ];
+
+    #[test]
+    fn explores_the_whole_maze() {
+        let maze = Explorer::new(MapDroid::new(&MAP)).explore();
+        assert!(maze.is_fully_explored());
+        assert_eq!(maze.home(), Some((0, 0)));
+        assert_eq!(maze.oxygen_system(), Some((2, 0)));
+        assert_eq!(maze.status((1, 0)), PositionStatus::Wall);
+        assert_eq!(maze.status((3, 2)), PositionStatus::NotVisited);
+        assert_eq!(maze.bounds(), Some(((-1, -3), (5, 2))));
+        assert_eq!(maze.to_string(), "\
+|+█████+|
+|█.....█|
+|█.███.█|
+|█X█o..█|
+|█...█.█|
+|+███+█+|
+");
+    }
+
+    #[test]
+    fn finds_shortest_route_to_oxygen_system() {
+        let maze = Explorer::new(MapDroid::new(&MAP)).explore();
+        let route = maze.route_to_oxygen_system().unwrap();
+        assert_eq!(route.iter().map(|x| x.to_string()).collect::<String>(), "SEEN");
+
+        let mut droid = MapDroid::new(&MAP);
+        let replies: Vec<Reply> = route.iter().map(|x| droid.send(*x)).collect();
+        assert_eq!(replies.last(), Some(&Reply::FoundOxygenSystem));
+        assert!(replies[..replies.len() - 1].iter().all(|x| *x == Reply::Moved));
+        assert_eq!(maze.route((0, 0), (0, 0)), Some(vec![]));
+        assert_eq!(maze.route((0, 0), (9, 9)), None);
+    }
+
+    #[test]
+    fn tracks_unknown_positions() {
+        let mut explorer = Explorer::new(MapDroid::new(&MAP));
+        assert_eq!(explorer.maze().unknown(), vec![(0, -1), (-1, 0), (1, 0), (0, 1)]);
+        explorer.step();
+        assert_eq!(explorer.position(), (0, -1));
+        assert_eq!(explorer.maze().unknown(), vec![(0, -2), (-1, -1), (1, -1), (-1, 0), (1, 0), (0, 1)]);
+        assert!(!explorer.maze().is_fully_explored());
+        assert_eq!(explorer.maze().route_to_oxygen_system(), None);
+    }
+
+    #[test]
+    fn reads_rendered_maze_back() {
+        let maze = Explorer::new(MapDroid::new(&MAP)).explore();
+        let parsed = Maze::parse(&maze.render()).unwrap();
+        assert_eq!(parsed.render(), maze.render());
+        assert_eq!(parsed.blocks().len(), maze.blocks().len());
+        assert!(maze.blocks().values().all(|x| parsed.status(x.position) == x.status));
+        assert_eq!(parsed.home(), Some((0, 0)));
+        assert_eq!(parsed.oxygen_system(), Some((2, 0)));
+        assert_eq!(parsed.route_to_oxygen_system(), maze.route_to_oxygen_system());
+    }
+
+    #[test]
+    fn places_home_at_the_origin() {
+        let maze = Maze::parse("+++\n+██\n█o.\n++X").unwrap();
+        assert_eq!(maze.home(), Some((0, 0)));
+        assert_eq!(maze.oxygen_system(), Some((-1, -1)));
+        assert_eq!(maze.status((-1, -2)), PositionStatus::Wall);
+        assert_eq!(maze.status((-2, -3)), PositionStatus::NotVisited);
+        assert_eq!(maze.bounds(), Some(((-2, -2), (0, 0))));
+        assert_eq!(maze.render(), "|+██|\n|█o.|\n|++X|\n");
+    }
+
+    #[test]
+    fn reports_bad_mazes() {
+        assert_eq!(Maze::parse("X.\n.#"), Err(MazeError::InvalidGlyph { line: 2, column: 2, glyph: '#' }));
+        assert_eq!(Maze::parse("|X.|\n|.X|"), Err(MazeError::DuplicateGlyph { line: 2, column: 2, glyph: 'X' }));
+        assert_eq!(Maze::parse("o.o"), Err(MazeError::DuplicateGlyph { line: 1, column: 3, glyph: 'o' }));
+        assert_eq!(Maze::parse("█.o"), Err(MazeError::MissingHome));
+        assert_eq!(Maze::parse(""), Err(MazeError::MissingHome));
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::{HashMap, HashSet};
+use std::{fmt, fs, io};
+use std::path::Path;
+use serde_json::{json, Value};
+use crate::bisect::max_within_budget;
+
+pub const ORE: &str = "ORE";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Chemical {
+    pub name: String,
+    pub quantity: u64,
+}
+
+impl Chemical {
+    // Reads `7 A`.
+    pub fn parse(chemical: &str) -> Result<Chemical, ReactionError> {
+        let invalid = || ReactionError::InvalidChemical(chemical.trim().to_string());
+        match chemical.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [quantity, name] => {
+                let quantity = quantity.parse::<u64>().map_err(|_| invalid())?;
+                if quantity == 0 { return Err(invalid()); }
+                Ok(Chemical { name: name.to_string(), quantity })
+            }
+            _ => Err(invalid())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reaction {
+    pub inputs: Vec<Chemical>,
+    pub output: Chemical,
+}
+
+impl Reaction {
+    // Reads `7 A, 1 B => 1 C`.
+    pub fn parse(reaction: &str) -> Result<Reaction, ReactionError> {
+        let (inputs, output) = reaction.split_once("=>")
+            .ok_or_else(|| ReactionError::InvalidReaction(reaction.trim().to_string()))?;
+        let inputs = inputs.split(',').map(Chemical::parse).collect::<Result<Vec<Chemical>, ReactionError>>()?;
+        Ok(Reaction { inputs, output: Chemical::parse(output)? })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ReactionError {
+    InvalidChemical(String),
+    InvalidReaction(String),
+    DuplicateProducer(String),
+    // A chemical used as an input that no reaction produces.
+    MissingProducer(String),
+    // Chemicals that are each needed to make the next, the last one needed for the first.
+    Cycle(Vec<String>),
+    UnknownChemical(String),
+    // Quantities beyond u64 while planning.
+    Overflow,
+}
+
+impl fmt::Display for ReactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReactionError::InvalidChemical(chemical) => write!(f, "Expected a quantity and a name, found {:?}", chemical),
+            ReactionError::InvalidReaction(reaction) => write!(f, "Expected inputs => output, found {:?}", reaction),
+            ReactionError::DuplicateProducer(name) => write!(f, "More than one reaction produces {}", name),
+            ReactionError::MissingProducer(name) => write!(f, "No reaction produces {}", name),
+            ReactionError::Cycle(names) => write!(f, "Chemicals are needed to produce themselves: {}", names.join(" <= ")),
+            ReactionError::UnknownChemical(name) => write!(f, "{} is neither ORE nor produced by a reaction", name),
+            ReactionError::Overflow => write!(f, "Quantities are too large to plan"),
+        }
+    }
+}
+
+impl std::error::Error for ReactionError {}
+
+// Reactions indexed by what they produce, with every chemical ordered so that it comes
+// before all the chemicals needed to make it. ORE comes last.
+pub struct Nanofactory {
+    reactions: HashMap<String, Reaction>,
+    order: Vec<String>,
+}
+
+impl Nanofactory {
+    pub fn parse(reactions: &str) -> Result<Nanofactory, ReactionError> {
+        let reactions = reactions.lines()
+            .filter(|x| !x.trim().is_empty())
+            .map(Reaction::parse)
+            .collect::<Result<Vec<Reaction>, ReactionError>>()?;
+        Nanofactory::new(reactions)
+    }
+
+    pub fn new(reactions: Vec<Reaction>) -> Result<Nanofactory, ReactionError> {
+        let mut by_output: HashMap<String, Reaction> = HashMap::new();
+        for reaction in reactions {
+            let name = reaction.output.name.clone();
+            if name == ORE || by_output.insert(name.clone(), reaction).is_some() {
+                return Err(ReactionError::DuplicateProducer(name));
+            }
+        }
+
+        let mut consumers: HashMap<&str, usize> = by_output.keys().map(|x| (x.as_str(), 0)).collect();
+        consumers.insert(ORE, 0);
+        for input in by_output.values().flat_map(|x| &x.inputs) {
+            *consumers.get_mut(input.name.as_str()).ok_or_else(|| ReactionError::MissingProducer(input.name.clone()))? += 1;
+        }
+
+        // Kahn's algorithm: a chemical is ready once every reaction consuming it has been placed.
+        let mut ready = consumers.iter().filter(|(_, x)| **x == 0).map(|(x, _)| *x).collect::<Vec<&str>>();
+        ready.sort_unstable_by(|a, b| b.cmp(a));
+        let mut order: Vec<String> = vec![];
+        while let Some(name) = ready.pop() {
+            order.push(name.to_string());
+            for input in by_output.get(name).map(|x| x.inputs.as_slice()).unwrap_or(&[]) {
+                let remaining = consumers.get_mut(input.name.as_str()).unwrap();
+                *remaining -= 1;
+                if *remaining == 0 { ready.push(&input.name); }
+            }
+        }
+
+        if order.len() < consumers.len() {
+            let placed = order.iter().map(|x| x.as_str()).collect::<HashSet<&str>>();
+            return Err(ReactionError::Cycle(Nanofactory::find_cycle(&by_output, &placed)));
+        }
+        Ok(Nanofactory { reactions: by_output, order })
+    }
+
+    // Every chemical left unplaced is still consumed by another unplaced reaction, so
+    // following consumers among them has to come back around. The cycle is reported with
+    // each chemical made from the next, starting from the first by name.
+    fn find_cycle(reactions: &HashMap<String, Reaction>, placed: &HashSet<&str>) -> Vec<String> {
+        let unplaced = |name: &str| !placed.contains(name) && reactions.contains_key(name);
+        let mut consumers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (output, reaction) in reactions.iter().filter(|(x, _)| unplaced(x)) {
+            for input in reaction.inputs.iter().filter(|x| unplaced(&x.name)) {
+                consumers.entry(input.name.as_str()).or_default().push(output.as_str());
+            }
+        }
+
+        let mut path: Vec<&str> = vec![consumers.keys().min().unwrap()];
+        loop {
+            let last = path[path.len() - 1];
+            let next = *consumers[last].iter().min().unwrap();
+            if let Some(start) = path.iter().position(|x| *x == next) {
+                let mut cycle: Vec<String> = path[start..].iter().rev().map(|x| x.to_string()).collect();
+                let first = (0..cycle.len()).min_by_key(|x| &cycle[*x]).unwrap();
+                cycle.rotate_left(first);
+                return cycle;
+            }
+            path.push(next);
+        }
+    }
+
+    pub fn reaction(&self, chemical: &str) -> Option<&Reaction> {
+        self.reactions.get(chemical)
+    }
+
+    // Chemicals, each before everything needed to produce it.
+    pub fn order(&self) -> &[String] {
+        &self.order
+    }
+
+    pub fn ore_required(&self, chemical: &str, quantity: u64) -> Result<u64, ReactionError> {
+        self.ore_required_from(chemical, quantity, &mut HashMap::new())
+    }
+
+    // Uses up leftovers from earlier runs first and adds this run's leftovers to them.
+    pub fn ore_required_from(&self, chemical: &str, quantity: u64, leftovers: &mut HashMap<String, u64>) -> Result<u64, ReactionError> {
+        self.plan_from(chemical, quantity, leftovers).map(|x| x.ore)
+    }
+
+    // Most of the chemical that can be made from the ORE, starting with nothing in stock.
+    pub fn max_producible(&self, chemical: &str, ore: u64) -> Result<u64, ReactionError> {
+        self.ore_required(chemical, 0)?;
+        Ok(max_within_budget(ore, |x| self.ore_required(chemical, x).ok()).unwrap())
+    }
+
+    pub fn plan(&self, chemical: &str, quantity: u64) -> Result<ProductionPlan, ReactionError> {
+        self.plan_from(chemical, quantity, &mut HashMap::new())
+    }
+
+    // A single pass over the order is enough since all the demand for a chemical is
+    // known once everything that consumes it has been handled.
+    pub fn plan_from(&self, chemical: &str, quantity: u64, leftovers: &mut HashMap<String, u64>) -> Result<ProductionPlan, ReactionError> {
+        if chemical != ORE && !self.reactions.contains_key(chemical) {
+            return Err(ReactionError::UnknownChemical(chemical.to_string()));
+        }
+
+        let mut needed: HashMap<&str, u64> = HashMap::new();
+        needed.insert(chemical, quantity);
+        let mut steps = vec![];
+        for name in self.order.iter().filter(|x| *x != ORE) {
+            let need = match needed.get(name.as_str()) { Some(x) => *x, None => continue };
+            let stock = leftovers.entry(name.clone()).or_insert(0);
+            let used = need.min(*stock);
+            *stock -= used;
+
+            let reaction = &self.reactions[name];
+            let runs = (need - used).div_ceil(reaction.output.quantity);
+            let produced = runs.checked_mul(reaction.output.quantity).ok_or(ReactionError::Overflow)?;
+            *stock += produced - (need - used);
+            for input in &reaction.inputs {
+                let total = needed.entry(input.name.as_str()).or_insert(0);
+                *total = runs.checked_mul(input.quantity).and_then(|x| x.checked_add(*total)).ok_or(ReactionError::Overflow)?;
+            }
+            steps.push(ProductionStep {
+                chemical: name.clone(),
+                reactions: runs,
+                produced,
+                consumed: need,
+                leftover: *stock,
+            });
+        }
+
+        let ore = needed.get(ORE).copied().unwrap_or(0);
+        steps.push(ProductionStep { chemical: String::from(ORE), reactions: 0, produced: ore, consumed: ore, leftover: 0 });
+        Ok(ProductionPlan { target: Chemical { name: chemical.to_string(), quantity }, ore, steps })
+    }
+}
+
+// What happens to one chemical in a plan. Consumed counts what the target and the other
+// reactions asked for, leftovers from earlier runs included, and for the target itself
+// it's the quantity delivered.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProductionStep {
+    pub chemical: String,
+    pub reactions: u64,
+    pub produced: u64,
+    pub consumed: u64,
+    pub leftover: u64,
+}
+
+// The bill of materials for a quantity of a chemical, in the nanofactory's order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProductionPlan {
+    pub target: Chemical,
+    pub ore: u64,
+    pub steps: Vec<ProductionStep>,
+}
+
+impl ProductionPlan {
+    pub fn step(&self, chemical: &str) -> Option<&ProductionStep> {
+        self.steps.iter().find(|x| x.chemical == chemical)
+    }
+
+    pub fn leftovers(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.steps.iter().filter(|x| x.leftover > 0).map(|x| (x.chemical.as_str(), x.leftover))
+    }
+
+    pub fn to_table(&self) -> String {
+        let headers = ["Chemical", "Reactions", "Produced", "Consumed", "Leftover"];
+        let rows = self.steps.iter()
+            .map(|x| vec![x.chemical.clone(), x.reactions.to_string(), x.produced.to_string(), x.consumed.to_string(), x.leftover.to_string()])
+            .collect::<Vec<Vec<String>>>();
+        let widths = (0..headers.len())
+            .map(|column| rows.iter().map(|x| x[column].len()).chain(std::iter::once(headers[column].len())).max().unwrap())
+            .collect::<Vec<usize>>();
+
+        // Names are left aligned, numbers right aligned.
+        let line = |cells: Vec<&str>| cells.iter().enumerate()
+            .map(|(column, cell)| match column {
+                0 => format!("{:<width$}", cell, width = widths[column]),
+                _ => format!("{:>width$}", cell, width = widths[column]),
+            })
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string();
+
+        let mut table = vec![
+            format!("{} {} from {} ORE", self.target.quantity, self.target.name, self.ore),
+            line(headers.to_vec()),
+            line(widths.iter().map(|x| "-".repeat(*x)).collect::<Vec<String>>().iter().map(|x| x.as_str()).collect()),
+        ];
+        table.extend(rows.iter().map(|x| line(x.iter().map(|x| x.as_str()).collect())));
+        table.join("\n")
+    }
+
+    pub fn to_value(&self) -> Value {
+        json!({
+            "target": { "name": self.target.name, "quantity": self.target.quantity },
+            "ore": self.ore,
+            "steps": self.steps.iter().map(|x| json!({
+                "chemical": x.chemical,
+                "reactions": x.reactions,
+                "produced": x.produced,
+                "consumed": x.consumed,
+                "leftover": x.leftover,
+            })).collect::<Vec<Value>>(),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_value()).unwrap()
+    }
+
+    pub fn save_json(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+impl fmt::Display for ProductionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_table())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nanofactory::{Chemical, Nanofactory, ProductionStep, Reaction, ReactionError, ORE};
+    use std::collections::HashMap;
+
+    const SMALL: &str = "9 ORE => 2 A
+        8 ORE => 3 B
+        7 ORE => 5 C
+        3 A, 4 B => 1 AB
+        5 B, 7 C => 1 BC
+        4 C, 1 A => 1 CA
+        2 AB, 3 BC, 4 CA => 1 FUEL";
+
+    #[test]
+    fn parses_reactions() {
+        assert_eq!(Reaction::parse("3 A, 4 B => 1 AB").unwrap(), Reaction {
+            inputs: vec![Chemical { name: String::from("A"), quantity: 3 }, Chemical { name: String::from("B"), quantity: 4 }],
+            output: Chemical { name: String::from("AB"), quantity: 1 },
+        });
+        assert_eq!(Reaction::parse("3 A 4 B"), Err(ReactionError::InvalidReaction(String::from("3 A 4 B"))));
+        assert_eq!(Reaction::parse("3 A, B => 1 AB"), Err(ReactionError::InvalidChemical(String::from("B"))));
+        assert_eq!(Reaction::parse("0 A => 1 AB"), Err(ReactionError::InvalidChemical(String::from("0 A"))));
+    }
+
+    #[test]
+    fn orders_chemicals_before_their_inputs() {
+        let factory = Nanofactory::parse(SMALL).unwrap();
+        let order = factory.order();
+        let position = |name: &str| order.iter().position(|x| x == name).unwrap();
+        assert_eq!(order.len(), 8);
+        assert_eq!(order[0], "FUEL");
+        assert_eq!(order[7], ORE);
+        assert!(position("AB") < position("A") && position("CA") < position("A") && position("BC") < position("C"));
+    }
+
+    #[test]
+    fn computes_ore_requirements() {
+        let factory = Nanofactory::parse(SMALL).unwrap();
+        assert_eq!(factory.ore_required("FUEL", 1), Ok(165));
+        assert_eq!(factory.ore_required("A", 3), Ok(18));
+        assert_eq!(factory.ore_required(ORE, 4), Ok(4));
+        assert_eq!(factory.ore_required("FUEL", 1_000_000_000_000), Ok(158_133_333_333_336));
+        assert_eq!(factory.ore_required("X", 1), Err(ReactionError::UnknownChemical(String::from("X"))));
+
+        let mut leftovers = HashMap::new();
+        assert_eq!(factory.ore_required_from("A", 1, &mut leftovers), Ok(9));
+        assert_eq!(leftovers["A"], 1);
+        assert_eq!(factory.ore_required_from("A", 1, &mut leftovers), Ok(0));
+        assert_eq!(leftovers["A"], 0);
+    }
+
+    #[test]
+    fn reports_invalid_reaction_sets() {
+        assert_eq!(Nanofactory::parse("1 ORE => 1 A\n2 ORE => 1 A").err(), Some(ReactionError::DuplicateProducer(String::from("A"))));
+        assert_eq!(Nanofactory::parse("1 A => 1 ORE").err(), Some(ReactionError::DuplicateProducer(String::from(ORE))));
+        assert_eq!(Nanofactory::parse("1 B => 1 FUEL").err(), Some(ReactionError::MissingProducer(String::from("B"))));
+        assert_eq!(Nanofactory::parse("1 A => 1 FUEL\n1 B, 1 ORE => 1 A\n1 C => 1 B\n2 A => 1 C").err(),
+                   Some(ReactionError::Cycle(vec![String::from("A"), String::from("B"), String::from("C")])));
+        // AA comes first by name but only needs ORE, so the cycle can't be found from it.
+        assert_eq!(Nanofactory::parse("1 ORE => 1 AA\n1 AA, 1 C => 1 B\n1 B => 1 C\n1 B => 1 FUEL").err(),
+                   Some(ReactionError::Cycle(vec![String::from("B"), String::from("C")])));
+    }
+
+    #[test]
+    fn finds_most_producible() {
+        let factory = Nanofactory::parse(SMALL).unwrap();
+        assert_eq!(factory.max_producible("FUEL", 164), Ok(0));
+        assert_eq!(factory.max_producible("FUEL", 165), Ok(1));
+        assert_eq!(factory.max_producible("A", 100), Ok(22));
+        assert_eq!(factory.max_producible(ORE, 100), Ok(100));
+        assert_eq!(factory.max_producible("X", 100), Err(ReactionError::UnknownChemical(String::from("X"))));
+        assert_eq!(factory.max_producible("FUEL", 1_000_000_000_000), Ok(6_323_777_403));
+        assert!(factory.ore_required("FUEL", 6_323_777_403).unwrap() <= 1_000_000_000_000);
+        assert!(factory.ore_required("FUEL", 6_323_777_404).unwrap() > 1_000_000_000_000);
+        assert_eq!(factory.ore_required("FUEL", u64::MAX), Err(ReactionError::Overflow));
+
+        let cheap = Nanofactory::parse("1 ORE => 10 A\n1 A => 1 FUEL").unwrap();
+        assert_eq!(cheap.max_producible("FUEL", 1), Ok(10));
+        assert_eq!(cheap.max_producible("FUEL", u64::MAX / 5), Ok(u64::MAX - 5));
+    }
+
+    #[test]
+    fn plans_production() {
+        let plan = Nanofactory::parse(SMALL).unwrap().plan("FUEL", 1).unwrap();
+        assert_eq!(plan.ore, 165);
+        assert_eq!(plan.steps[0], ProductionStep { chemical: String::from("FUEL"), reactions: 1, produced: 1, consumed: 1, leftover: 0 });
+        assert_eq!(plan.step("A"), Some(&ProductionStep { chemical: String::from("A"), reactions: 5, produced: 10, consumed: 10, leftover: 0 }));
+        assert_eq!(plan.step("B"), Some(&ProductionStep { chemical: String::from("B"), reactions: 8, produced: 24, consumed: 23, leftover: 1 }));
+        assert_eq!(plan.step(ORE), Some(&ProductionStep { chemical: String::from(ORE), reactions: 0, produced: 165, consumed: 165, leftover: 0 }));
+        assert_eq!(plan.leftovers().collect::<Vec<(&str, u64)>>(), vec![("C", 3), ("B", 1)]);
+        assert!(plan.steps.iter().all(|x| x.produced >= x.consumed));
+    }
+
+    #[test]
+    fn reports_plans() {
+        let plan = Nanofactory::parse("10 ORE => 10 A\n7 A => 1 FUEL").unwrap().plan("FUEL", 2).unwrap();
+        assert_eq!(plan.to_table(), "2 FUEL from 20 ORE
+Chemical  Reactions  Produced  Consumed  Leftover
+--------  ---------  --------  --------  --------
+FUEL              2         2         2         0
+A                 2        20        14         6
+ORE               0        20        20         0");
+        assert_eq!(plan.to_string(), plan.to_table());
+
+        let json: serde_json::Value = serde_json::from_str(&plan.to_json()).unwrap();
+        assert_eq!(json["ore"], 20);
+        assert_eq!(json["target"]["name"], "FUEL");
+        assert_eq!(json["steps"][1]["chemical"], "A");
+        assert_eq!(json["steps"][1]["leftover"], 6);
+    }
+}
@@ -0,0 +1,375 @@
+use std::convert::TryInto;
+use std::{fmt, fs, io};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::thread;
+use plotlib::line::{self, Line};
+use plotlib::page::Page;
+use plotlib::style::Line as LineStyle;
+use plotlib::view::ContinuousView;
+use regex::Regex;
+use crate::cycles::{brent, Cycle};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Body<const D: usize> {
+    pub position: [i64; D],
+    pub velocity: [i64; D],
+}
+
+impl<const D: usize> Body<D> {
+    pub fn at_rest(position: [i64; D]) -> Body<D> {
+        Body { position, velocity: [0; D] }
+    }
+
+    pub fn potential_energy(&self) -> i64 {
+        self.position.iter().map(|x| x.abs()).sum()
+    }
+
+    pub fn kinetic_energy(&self) -> i64 {
+        self.velocity.iter().map(|x| x.abs()).sum()
+    }
+
+    pub fn total_energy(&self) -> i64 {
+        self.potential_energy() * self.kinetic_energy()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NBodyError {
+    // Lines are counted from 1.
+    WrongDimensions { line: usize, found: usize, expected: usize },
+}
+
+impl fmt::Display for NBodyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NBodyError::WrongDimensions { line, found, expected } =>
+                write!(f, "Line {} has {} coordinates instead of {}", line, found, expected),
+        }
+    }
+}
+
+impl std::error::Error for NBodyError {}
+
+// Bodies pulling each other one unit per step along every axis. The axes never affect
+// each other, so each one can be simulated on its own.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NBody<const D: usize> {
+    bodies: Vec<Body<D>>,
+    steps: u64,
+}
+
+impl<const D: usize> NBody<D> {
+    pub fn new(bodies: Vec<Body<D>>) -> NBody<D> {
+        NBody { bodies, steps: 0 }
+    }
+
+    // Reads one body at rest per line, like `<x=-1, y=0, z=2>`, taking its numbers in order.
+    pub fn parse(input: &str) -> Result<NBody<D>, NBodyError> {
+        let number = Regex::new(r"-?\d+").unwrap();
+        let bodies = input.lines()
+            .enumerate()
+            .filter(|(_, x)| !x.trim().is_empty())
+            .map(|(line, text)| {
+                let numbers = number.find_iter(text).map(|x| x.as_str().parse().unwrap()).collect::<Vec<i64>>();
+                let position = numbers.as_slice().try_into()
+                    .map_err(|_| NBodyError::WrongDimensions { line: line + 1, found: numbers.len(), expected: D })?;
+                Ok(Body::at_rest(position))
+            })
+            .collect::<Result<Vec<Body<D>>, NBodyError>>()?;
+        Ok(NBody::new(bodies))
+    }
+
+    pub fn bodies(&self) -> &[Body<D>] {
+        &self.bodies
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn step(&mut self) {
+        for axis in 0..D {
+            let (mut positions, mut velocities) = self.axis(axis);
+            step_axis(&mut positions, &mut velocities);
+            for (body, (position, velocity)) in self.bodies.iter_mut().zip(positions.into_iter().zip(velocities)) {
+                body.position[axis] = position;
+                body.velocity[axis] = velocity;
+            }
+        }
+        self.steps += 1;
+    }
+
+    pub fn simulate(&mut self, steps: u64) {
+        (0..steps).for_each(|_| self.step());
+    }
+
+    // Positions and velocities of every body along one axis.
+    pub fn axis(&self, axis: usize) -> (Vec<i64>, Vec<i64>) {
+        self.bodies.iter().map(|x| (x.position[axis], x.velocity[axis])).unzip()
+    }
+
+    pub fn total_energy(&self) -> i64 {
+        self.bodies.iter().map(|x| x.total_energy()).sum()
+    }
+
+    // Where one axis starts repeating, found with Brent's algorithm so a tail before the
+    // cycle is reported too.
+    pub fn axis_cycle(&self, axis: usize) -> Cycle {
+        brent(&self.axis(axis), |(positions, velocities)| {
+            let (mut positions, mut velocities) = (positions.clone(), velocities.clone());
+            step_axis(&mut positions, &mut velocities);
+            (positions, velocities)
+        })
+    }
+
+    // Each axis is searched on its own thread.
+    pub fn axis_cycles(&self) -> Vec<Cycle> {
+        thread::scope(|scope| {
+            let searches = (0..D).map(|x| scope.spawn(move || self.axis_cycle(x))).collect::<Vec<_>>();
+            searches.into_iter().map(|x| x.join().unwrap()).collect()
+        })
+    }
+
+    // Where the whole system starts repeating. Steps can be undone, since the old velocities
+    // follow from the new positions, so for this gravity the tail is always 0.
+    pub fn cycle(&self) -> Cycle {
+        Cycle::combine(self.axis_cycles())
+    }
+}
+
+// Sorting once finds for every body how many others are above and below it, so gravity
+// takes O(n log n) rather than comparing every pair.
+pub fn step_axis(positions: &mut [i64], velocities: &mut [i64]) {
+    let mut order = (0..positions.len()).collect::<Vec<usize>>();
+    order.sort_unstable_by_key(|x| positions[*x]);
+
+    let mut start = 0;
+    while start < order.len() {
+        let end = start + order[start..].iter().take_while(|x| positions[**x] == positions[order[start]]).count();
+        let pull = (order.len() - end) as i64 - start as i64;
+        order[start..end].iter().for_each(|x| velocities[*x] += pull);
+        start = end;
+    }
+    positions.iter_mut().zip(velocities.iter()).for_each(|(position, velocity)| *position += velocity);
+}
+
+// The system as it was at one step.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Sample<const D: usize> {
+    pub step: u64,
+    pub bodies: Vec<Body<D>>,
+}
+
+impl<const D: usize> Sample<D> {
+    pub fn potential_energy(&self) -> i64 {
+        self.bodies.iter().map(|x| x.potential_energy()).sum()
+    }
+
+    pub fn kinetic_energy(&self) -> i64 {
+        self.bodies.iter().map(|x| x.kinetic_energy()).sum()
+    }
+
+    pub fn total_energy(&self) -> i64 {
+        self.bodies.iter().map(|x| x.total_energy()).sum()
+    }
+}
+
+// Keeps every `interval`th step within a range of steps, counting from the start of the range.
+pub struct EnergyRecorder<const D: usize> {
+    interval: u64,
+    steps: RangeInclusive<u64>,
+    samples: Vec<Sample<D>>,
+}
+
+impl<const D: usize> Default for EnergyRecorder<D> {
+    fn default() -> Self {
+        EnergyRecorder::new()
+    }
+}
+
+impl<const D: usize> EnergyRecorder<D> {
+    pub fn new() -> EnergyRecorder<D> {
+        EnergyRecorder { interval: 1, steps: 0..=1000, samples: vec![] }
+    }
+
+    pub fn interval(mut self, interval: u64) -> EnergyRecorder<D> {
+        self.interval = interval.max(1);
+        self
+    }
+
+    pub fn steps(mut self, steps: RangeInclusive<u64>) -> EnergyRecorder<D> {
+        self.steps = steps;
+        self
+    }
+
+    pub fn samples(&self) -> &[Sample<D>] {
+        &self.samples
+    }
+
+    pub fn record(&mut self, system: &NBody<D>) {
+        let step = system.steps();
+        if self.steps.contains(&step) && (step - self.steps.start()).is_multiple_of(self.interval) {
+            self.samples.push(Sample { step, bodies: system.bodies().to_vec() });
+        }
+    }
+
+    // Simulates up to the end of the range, recording along the way.
+    pub fn run(&mut self, system: &mut NBody<D>) {
+        self.record(system);
+        while system.steps() < *self.steps.end() {
+            system.step();
+            self.record(system);
+        }
+    }
+
+    // One row per body per sample, with the system's energies repeated on each row so
+    // any row can be read on its own.
+    pub fn to_csv(&self) -> String {
+        let axes = (0..D).map(axis_name).collect::<Vec<String>>();
+        let header = ["step", "body"].iter().map(|x| x.to_string())
+            .chain(axes.iter().cloned())
+            .chain(axes.iter().map(|x| format!("v{}", x)))
+            .chain(["potential", "kinetic", "total", "system_potential", "system_kinetic", "system_total"].iter().map(|x| x.to_string()))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let mut csv = vec![header];
+        for sample in &self.samples {
+            let system = [sample.potential_energy(), sample.kinetic_energy(), sample.total_energy()];
+            for (index, body) in sample.bodies.iter().enumerate() {
+                let row = [sample.step as i64, index as i64].iter()
+                    .chain(body.position.iter())
+                    .chain(body.velocity.iter())
+                    .chain([body.potential_energy(), body.kinetic_energy(), body.total_energy()].iter())
+                    .chain(system.iter())
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>();
+                csv.push(row.join(","));
+            }
+        }
+        csv.join("\n") + "\n"
+    }
+
+    pub fn save_csv(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    // Potential, kinetic and total energy of the system over time.
+    pub fn save_energy_chart(&self, path: &Path) -> Result<(), String> {
+        let series = |energy: fn(&Sample<D>) -> i64| self.samples.iter()
+            .map(|x| (x.step as f64, energy(x) as f64))
+            .collect::<Vec<(f64, f64)>>();
+        let potential = Line::new(&series(Sample::potential_energy)).style(line::Style::new().colour("#1F77B4"));
+        let kinetic = Line::new(&series(Sample::kinetic_energy)).style(line::Style::new().colour("#FF7F0E"));
+        let total = Line::new(&series(Sample::total_energy)).style(line::Style::new().colour("#2CA02C").width(2.));
+        let view = ContinuousView::new()
+            .add(&potential)
+            .add(&kinetic)
+            .add(&total)
+            .x_label("Step")
+            .y_label("Energy");
+        Page::single(&view).save(path).map_err(|x| x.to_string())
+    }
+}
+
+fn axis_name(axis: usize) -> String {
+    match axis {
+        0 => String::from("x"),
+        1 => String::from("y"),
+        2 => String::from("z"),
+        _ => format!("axis{}", axis),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cycles::Cycle;
+    use crate::nbody::{step_axis, Body, EnergyRecorder, NBody, NBodyError};
+
+    const EXAMPLE: &str = "<x=-1, y=0, z=2>\n<x=2, y=-10, z=-7>\n<x=4, y=-8, z=8>\n<x=3, y=5, z=-1>";
+
+    #[test]
+    fn parses_bodies() {
+        let system = NBody::<3>::parse(EXAMPLE).unwrap();
+        assert_eq!(system.bodies()[1], Body { position: [2, -10, -7], velocity: [0, 0, 0] });
+        assert_eq!(NBody::<2>::parse("<x=1, y=2>\n\n<x=3, y=-4>").unwrap().bodies().len(), 2);
+        assert_eq!(NBody::<3>::parse("<x=1, y=2, z=3>\n<x=1, y=2>").err(),
+                   Some(NBodyError::WrongDimensions { line: 2, found: 2, expected: 3 }));
+    }
+
+    #[test]
+    fn applies_gravity_by_rank() {
+        let mut positions = vec![3, 5, 3, 1, 9];
+        let mut velocities = vec![0, 1, 0, 0, -1];
+        step_axis(&mut positions, &mut velocities);
+        assert_eq!(velocities, vec![1, -1, 1, 4, -5]);
+        assert_eq!(positions, vec![4, 4, 4, 5, 4]);
+
+        // Matches comparing every pair.
+        let mut positions = (0..50).map(|x| (x * 37 % 23) - 11).collect::<Vec<i64>>();
+        let mut velocities = vec![0; 50];
+        let pairwise = positions.iter().map(|a| positions.iter().map(|b| (b - a).signum()).sum()).collect::<Vec<i64>>();
+        step_axis(&mut positions, &mut velocities);
+        assert_eq!(velocities, pairwise);
+    }
+
+    #[test]
+    fn simulates_example() {
+        let mut system = NBody::<3>::parse(EXAMPLE).unwrap();
+        system.simulate(10);
+        assert_eq!(system.steps(), 10);
+        assert_eq!(system.bodies()[3], Body { position: [2, 0, 4], velocity: [1, -1, -1] });
+        assert_eq!(system.total_energy(), 179);
+
+        let system = NBody::<3>::parse(EXAMPLE).unwrap();
+        assert_eq!(system.axis_cycles(), vec![Cycle { tail: 0, period: 18 }, Cycle { tail: 0, period: 28 }, Cycle { tail: 0, period: 44 }]);
+        assert_eq!(system.cycle(), Cycle { tail: 0, period: 2772 });
+    }
+
+    #[test]
+    fn handles_other_sizes() {
+        let mut line = NBody::new(vec![Body::at_rest([0]), Body::at_rest([4])]);
+        assert_eq!(line.cycle(), Cycle { tail: 0, period: 8 });
+        line.simulate(8);
+        assert_eq!(line.bodies(), &[Body::at_rest([0]), Body::at_rest([4])]);
+
+        let plane = NBody::new((0..7).map(|x| Body::at_rest([x * 3 % 5, x * x % 7])).collect());
+        let cycle = plane.cycle();
+        assert_eq!(cycle.period, num::integer::lcm(plane.axis_cycle(0).period, plane.axis_cycle(1).period));
+        let mut moved = plane.clone();
+        moved.simulate(cycle.first_repeat());
+        assert_eq!(moved.bodies(), plane.bodies());
+    }
+
+    #[test]
+    fn records_samples_in_range() {
+        let mut system = NBody::<3>::parse(EXAMPLE).unwrap();
+        let mut recorder = EnergyRecorder::new().interval(3).steps(2..=10);
+        recorder.run(&mut system);
+        assert_eq!(system.steps(), 10);
+        assert_eq!(recorder.samples().iter().map(|x| x.step).collect::<Vec<u64>>(), vec![2, 5, 8]);
+
+        let mut system = NBody::<3>::parse(EXAMPLE).unwrap();
+        let mut recorder = EnergyRecorder::new().steps(0..=10);
+        recorder.run(&mut system);
+        let last = recorder.samples().last().unwrap();
+        assert_eq!(recorder.samples().len(), 11);
+        assert_eq!((last.step, last.total_energy()), (10, 179));
+        assert_eq!(last.potential_energy(), 6 + 9 + 10 + 6);
+        assert_eq!(last.kinetic_energy(), 6 + 5 + 8 + 3);
+    }
+
+    #[test]
+    fn writes_csv() {
+        let mut system = NBody::new(vec![Body::at_rest([0, 1]), Body::at_rest([2, 1])]);
+        let mut recorder = EnergyRecorder::new().steps(0..=1);
+        recorder.run(&mut system);
+        assert_eq!(recorder.to_csv(), "step,body,x,y,vx,vy,potential,kinetic,total,system_potential,system_kinetic,system_total
+0,0,0,1,0,0,1,0,0,4,0,0
+0,1,2,1,0,0,3,0,0,4,0,0
+1,0,1,1,1,0,2,1,2,4,2,4
+1,1,1,1,-1,0,2,1,2,4,2,4
+");
+    }
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::fmt;
+
+// The block letters Advent of Code draws with pixels. The small font is 6 pixels high and
+// mostly 4 wide, the large one is 10 high and mostly 6 wide. Glyphs are stored without
+// blank columns on either side, as that's how they come out of `segment`.
+static SMALL_FONT: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+static LARGE_FONT: [(char, &str); 15] = [
+    ('A', "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#"),
+    ('B', "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####."),
+    ('C', ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####."),
+    ('E', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('F', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('G', ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#"),
+    ('H', "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#"),
+    ('J', "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###.."),
+    ('K', "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#"),
+    ('L', "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('N', "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#"),
+    ('P', "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('R', "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#"),
+    ('X', "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#"),
+    ('Z', "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnrecognizedGlyph {
+    pub position: usize,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OcrError {
+    // Only 6 and 10 pixel high text can be read, once blank rows are trimmed.
+    UnsupportedHeight(usize),
+    UnrecognizedGlyphs(Vec<UnrecognizedGlyph>),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::UnsupportedHeight(height) =>
+                write!(f, "Text is {} pixels high, expected 6 or 10", height),
+            OcrError::UnrecognizedGlyphs(glyphs) => {
+                write!(f, "Unrecognized glyphs at positions {:?}",
+                       glyphs.iter().map(|x| x.position).collect::<Vec<usize>>())?;
+                glyphs.iter().try_for_each(|x| write!(f, "\n\n{}", x.pattern))
+            }
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+// Reads text like "#..#\n####" where `lit` marks the pixels that are on.
+pub fn parse_grid(text: &str, lit: char) -> Vec<Vec<bool>> {
+    text.lines()
+        .filter(|x| !x.is_empty())
+        .map(|line| line.chars().map(|x| x == lit).collect())
+        .collect()
+}
+
+pub fn grid_to_string(grid: &[Vec<bool>]) -> String {
+    grid.iter()
+        .map(|row| row.iter().map(|x| if *x { '#' } else { '.' }).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Splits the grid into glyphs at blank columns, after trimming blank rows above and below
+// the text. Rows may have different lengths, missing pixels count as off.
+pub fn segment(grid: &[Vec<bool>]) -> Vec<Vec<Vec<bool>>> {
+    let is_blank_row = |row: &Vec<bool>| !row.iter().any(|x| *x);
+    let first_row = grid.iter().position(|x| !is_blank_row(x));
+    let last_row = grid.iter().rposition(|x| !is_blank_row(x));
+    let rows = match (first_row, last_row) {
+        (Some(first), Some(last)) => &grid[first..=last],
+        _ => return vec![]
+    };
+
+    let width = rows.iter().map(|x| x.len()).max().unwrap_or(0);
+    let pixel = |row: &Vec<bool>, column: usize| *row.get(column).unwrap_or(&false);
+    let is_blank_column = |column: usize| !rows.iter().any(|row| pixel(row, column));
+
+    let mut glyphs = vec![];
+    let mut start = None;
+    for column in 0..=width {
+        match (start, column == width || is_blank_column(column)) {
+            (None, false) => start = Some(column),
+            (Some(first), true) => {
+                glyphs.push(rows.iter()
+                    .map(|row| (first..column).map(|x| pixel(row, x)).collect())
+                    .collect());
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    glyphs
+}
+
+pub fn recognize(grid: &[Vec<bool>]) -> Result<String, OcrError> {
+    let glyphs = segment(grid);
+    let height = glyphs.first().map_or(0, |x| x.len());
+    let font: &[(char, &str)] = match height {
+        0 => return Ok(String::new()),
+        6 => &SMALL_FONT,
+        10 => &LARGE_FONT,
+        _ => return Err(OcrError::UnsupportedHeight(height))
+    };
+
+    let mut text = String::new();
+    let mut unrecognized = vec![];
+    for (position, glyph) in glyphs.iter().enumerate() {
+        let pattern = grid_to_string(glyph);
+        match font.iter().find(|(_, x)| *x == pattern) {
+            Some((letter, _)) => text.push(*letter),
+            None => unrecognized.push(UnrecognizedGlyph { position, pattern })
+        }
+    }
+
+    if unrecognized.is_empty() { Ok(text) } else { Err(OcrError::UnrecognizedGlyphs(unrecognized)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ocr::{parse_grid, recognize, segment, OcrError, UnrecognizedGlyph, LARGE_FONT, SMALL_FONT};
+
+    // Lays glyphs out side by side with `gap` blank columns between them.
+    fn render(font: &[(char, &str)], text: &str, gap: usize) -> String {
+        let glyphs = text.chars()
+            .map(|letter| font.iter().find(|x| x.0 == letter).unwrap().1.lines().collect::<Vec<&str>>())
+            .collect::<Vec<Vec<&str>>>();
+        (0..glyphs[0].len())
+            .map(|row| glyphs.iter().map(|x| x[row]).collect::<Vec<&str>>().join(&".".repeat(gap)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn recognizes_every_letter_of_both_fonts() {
+        let small = SMALL_FONT.iter().map(|x| x.0).collect::<String>();
+        assert_eq!(recognize(&parse_grid(&render(&SMALL_FONT, &small, 1), '#')), Ok(small));
+
+        let large = LARGE_FONT.iter().map(|x| x.0).collect::<String>();
+        assert_eq!(recognize(&parse_grid(&render(&LARGE_FONT, &large, 2), '#')), Ok(large));
+    }
+
+    #[test]
+    fn trims_blank_rows_and_columns() {
+        let text = format!("\n.........\n{}\n.........\n", render(&SMALL_FONT, "HI", 3)
+            .lines().map(|x| format!("..{}..", x)).collect::<Vec<String>>().join("\n"));
+        assert_eq!(segment(&parse_grid(&text, '#')).len(), 2);
+        assert_eq!(recognize(&parse_grid(&text, '#')), Ok(String::from("HI")));
+        assert_eq!(recognize(&[]), Ok(String::new()));
+    }
+
+    #[test]
+    fn lists_unrecognized_glyphs() {
+        let text = "#..#.#.#\n#..#....\n####...#\n#..#....\n#..#....\n#..#....";
+        assert_eq!(recognize(&parse_grid(text, '#')), Err(OcrError::UnrecognizedGlyphs(vec![
+            UnrecognizedGlyph { position: 1, pattern: String::from("#\n.\n.\n.\n.\n.") },
+            UnrecognizedGlyph { position: 2, pattern: String::from("#\n.\n#\n.\n.\n.") },
+        ])));
+        assert_eq!(recognize(&parse_grid("#\n#\n#", '#')), Err(OcrError::UnsupportedHeight(3)));
+    }
+}
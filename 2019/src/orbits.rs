@@ -0,0 +1,617 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::{fmt, fs, io, thread};
+use std::path::Path;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::search::Graph;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrbitError {
+    Empty,
+    // Lines are counted from 1.
+    InvalidLine { line: usize, text: String },
+    DuplicateParent { object: String, first: String, second: String },
+    MultipleRoots(Vec<String>),
+    // The objects on one cycle, starting from the first by name.
+    Cycle(Vec<String>),
+    Unreachable(Vec<String>),
+    InvalidJson(String),
+}
+
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::Empty => write!(f, "The orbit map is empty"),
+            OrbitError::InvalidLine { line, text } =>
+                write!(f, "Line {} should look like A)B, found {:?}", line, text),
+            OrbitError::DuplicateParent { object, first, second } =>
+                write!(f, "{} orbits both {} and {}", object, first, second),
+            OrbitError::MultipleRoots(roots) => write!(f, "More than one object orbits nothing: {}", roots.join(", ")),
+            OrbitError::Cycle(objects) => write!(f, "Objects orbit each other in a cycle: {}", objects.join(")")),
+            OrbitError::Unreachable(objects) => write!(f, "Objects can't be reached from the root: {}", objects.join(", ")),
+            OrbitError::InvalidJson(error) => write!(f, "The orbit map is not valid JSON: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for OrbitError {}
+
+// Objects orbiting each other as a tree, checked when it's built. Objects are numbered
+// in the order they are first seen.
+pub struct OrbitMap {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    root: usize,
+    depths: Vec<usize>,
+    // ancestors[k][object] is the ancestor 2^k orbits up, or the root once past it.
+    ancestors: Vec<Vec<usize>>,
+}
+
+// The nested form used for JSON, each object listing the objects orbiting it.
+// An object without satellites leaves the field out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitTree {
+    pub name: String,
+    pub satellites: Vec<OrbitTree>,
+}
+
+impl OrbitTree {
+    pub fn to_value(&self) -> Value {
+        match self.satellites.is_empty() {
+            true => json!({ "name": self.name }),
+            false => json!({ "name": self.name, "satellites": self.satellites.iter().map(|x| x.to_value()).collect::<Vec<Value>>() }),
+        }
+    }
+
+    pub fn from_value(value: &Value) -> Result<OrbitTree, String> {
+        let name = json_name(value)?;
+        let satellites = match value.get("satellites") {
+            None => vec![],
+            Some(Value::Array(satellites)) => satellites.iter().map(OrbitTree::from_value).collect::<Result<Vec<OrbitTree>, String>>()?,
+            Some(other) => return Err(format!("Satellites of {} should be a list, found {}", name, other)),
+        };
+        Ok(OrbitTree { name: name.to_string(), satellites })
+    }
+}
+
+impl OrbitMap {
+    // Reads lines like `COM)B`, meaning B orbits COM. Reports every problem found.
+    pub fn parse(map: &str) -> Result<OrbitMap, Vec<OrbitError>> {
+        let mut orbits = vec![];
+        let mut errors = vec![];
+        for (line, text) in map.lines().enumerate().filter(|(_, x)| !x.trim().is_empty()) {
+            match text.trim().split_once(')') {
+                Some((center, object)) if !center.is_empty() && !object.is_empty() && !object.contains(')') =>
+                    orbits.push((center.to_string(), object.to_string())),
+                _ => errors.push(OrbitError::InvalidLine { line: line + 1, text: text.to_string() })
+            }
+        }
+        if !errors.is_empty() { return Err(errors); }
+        OrbitMap::from_orbits(orbits)
+    }
+
+    // Builds the map from (center, object) pairs.
+    pub fn from_orbits(orbits: Vec<(String, String)>) -> Result<OrbitMap, Vec<OrbitError>> {
+        let mut names: Vec<String> = vec![];
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        let mut parents: Vec<Option<usize>> = vec![];
+        let mut errors = vec![];
+        for (center, object) in orbits {
+            let center = OrbitMap::index_of(center, &mut names, &mut indices);
+            let object = OrbitMap::index_of(object, &mut names, &mut indices);
+            parents.resize(names.len(), None);
+            match parents[object] {
+                Some(first) if first != center => errors.push(OrbitError::DuplicateParent {
+                    object: names[object].clone(),
+                    first: names[first].clone(),
+                    second: names[center].clone(),
+                }),
+                _ => parents[object] = Some(center)
+            }
+        }
+        if names.is_empty() { return Err(vec![OrbitError::Empty]); }
+
+        let roots = (0..names.len()).filter(|x| parents[*x].is_none()).collect::<Vec<usize>>();
+        if roots.len() > 1 {
+            errors.push(OrbitError::MultipleRoots(roots.iter().map(|x| names[*x].clone()).collect()));
+        }
+
+        let mut children: Vec<Vec<usize>> = vec![vec![]; names.len()];
+        (0..names.len()).for_each(|object| if let Some(parent) = parents[object] { children[parent].push(object) });
+
+        // Breadth first from the roots sets every depth in O(n), leaving out what hangs off a cycle.
+        let mut depths: Vec<Option<usize>> = vec![None; names.len()];
+        let mut queue = roots.iter().map(|x| (*x, 0)).collect::<VecDeque<(usize, usize)>>();
+        while let Some((object, depth)) = queue.pop_front() {
+            depths[object] = Some(depth);
+            queue.extend(children[object].iter().map(|x| (*x, depth + 1)));
+        }
+
+        let mut unreachable = (0..names.len()).filter(|x| depths[*x].is_none()).collect::<Vec<usize>>();
+        if !unreachable.is_empty() {
+            errors.push(OrbitError::Cycle(OrbitMap::find_cycle(&names, &parents, &unreachable)));
+            unreachable.sort_by_key(|x| &names[*x]);
+            errors.push(OrbitError::Unreachable(unreachable.iter().map(|x| names[*x].clone()).collect()));
+        }
+        if !errors.is_empty() { return Err(errors); }
+
+        let root = roots[0];
+        let depths = depths.into_iter().map(|x| x.unwrap()).collect::<Vec<usize>>();
+        let mut ancestors = vec![parents.iter().map(|x| x.unwrap_or(root)).collect::<Vec<usize>>()];
+        while 1 << ancestors.len() <= *depths.iter().max().unwrap() {
+            let previous = ancestors.last().unwrap();
+            ancestors.push(previous.iter().map(|x| previous[*x]).collect());
+        }
+
+        Ok(OrbitMap { names, indices, parents, children, root, depths, ancestors })
+    }
+
+    fn index_of(name: String, names: &mut Vec<String>, indices: &mut HashMap<String, usize>) -> usize {
+        *indices.entry(name.clone()).or_insert_with(|| {
+            names.push(name);
+            names.len() - 1
+        })
+    }
+
+    // Every unreachable object leads to a cycle when following what it orbits.
+    fn find_cycle(names: &[String], parents: &[Option<usize>], unreachable: &[usize]) -> Vec<String> {
+        let start = *unreachable.iter().min_by_key(|x| &names[**x]).unwrap();
+        let mut seen = vec![false; names.len()];
+        let mut object = start;
+        while !seen[object] {
+            seen[object] = true;
+            object = parents[object].unwrap();
+        }
+
+        let mut cycle = vec![object];
+        let mut next = parents[object].unwrap();
+        while next != object {
+            cycle.push(next);
+            next = parents[next].unwrap();
+        }
+        let first = (0..cycle.len()).min_by_key(|x| &names[cycle[*x]]).unwrap();
+        cycle.rotate_left(first);
+        cycle.iter().map(|x| names[*x].clone()).collect()
+    }
+
+    pub fn root(&self) -> &str {
+        &self.names[self.root]
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|x| x.as_str())
+    }
+
+    pub fn parent(&self, object: &str) -> Option<&str> {
+        self.parents[*self.indices.get(object)?].map(|x| self.names[x].as_str())
+    }
+
+    // The object followed by everything it orbits, ending at the root.
+    pub fn path_to_root(&self, object: &str) -> Option<Vec<&str>> {
+        let mut path = vec![self.names[*self.indices.get(object)?].as_str()];
+        while let Some(parent) = self.parent(path[path.len() - 1]) {
+            path.push(parent);
+        }
+        Some(path)
+    }
+
+    // Number of direct and indirect orbits of the object.
+    pub fn depth(&self, object: &str) -> Option<usize> {
+        self.indices.get(object).map(|x| self.depths[*x])
+    }
+
+    pub fn total_orbits(&self) -> usize {
+        self.depths.iter().sum()
+    }
+
+    pub fn lowest_common_ancestor(&self, object1: &str, object2: &str) -> Option<&str> {
+        let ancestor = self.lowest_common_ancestor_index(*self.indices.get(object1)?, *self.indices.get(object2)?);
+        Some(&self.names[ancestor])
+    }
+
+    // Orbits to go through from one object to the other, in O(log n).
+    pub fn distance(&self, object1: &str, object2: &str) -> Option<usize> {
+        let (object1, object2) = (*self.indices.get(object1)?, *self.indices.get(object2)?);
+        let ancestor = self.lowest_common_ancestor_index(object1, object2);
+        Some(self.depths[object1] + self.depths[object2] - 2 * self.depths[ancestor])
+    }
+
+    // Transfers needed to move from the object `object1` orbits to the one `object2` orbits.
+    pub fn orbital_transfers(&self, object1: &str, object2: &str) -> Option<usize> {
+        self.distance(self.parent(object1)?, self.parent(object2)?)
+    }
+
+    // Objects passed going from one object to the other through their lowest common ancestor,
+    // both ends included.
+    pub fn transfer_path(&self, object1: &str, object2: &str) -> Option<Vec<&str>> {
+        let ancestor = self.lowest_common_ancestor(object1, object2)?;
+        let mut path = self.path_to_root(object1)?;
+        path.truncate(path.iter().position(|x| *x == ancestor).unwrap() + 1);
+        let mut down = self.path_to_root(object2)?;
+        down.truncate(down.iter().position(|x| *x == ancestor).unwrap());
+        path.extend(down.into_iter().rev());
+        Some(path)
+    }
+
+    // Graphviz digraph with an edge from each object to its satellites. Giving two objects
+    // highlights the transfer path between them.
+    pub fn to_dot(&self, highlight: Option<(&str, &str)>) -> String {
+        let path = highlight.and_then(|(from, to)| self.transfer_path(from, to)).unwrap_or_default();
+        let edges = path.windows(2)
+            .map(|x| if self.parent(x[1]) == Some(x[0]) { (x[0], x[1]) } else { (x[1], x[0]) })
+            .collect::<HashSet<(&str, &str)>>();
+
+        let mut dot = vec![String::from("digraph orbits {"), String::from("    node [shape=circle];")];
+        if let (Some(first), Some(last)) = (path.first(), path.last()) {
+            dot.push(format!("    {} [style=filled, fillcolor=gold];", quote(first)));
+            dot.push(format!("    {} [style=filled, fillcolor=gold];", quote(last)));
+        }
+        let mut queue = VecDeque::from(vec![self.root]);
+        while let Some(center) = queue.pop_front() {
+            for object in &self.children[center] {
+                let (center, object) = (self.names[center].as_str(), self.names[*object].as_str());
+                let style = if edges.contains(&(center, object)) { " [color=red, penwidth=3]" } else { "" };
+                dot.push(format!("    {} -> {}{};", quote(center), quote(object), style));
+            }
+            queue.extend(&self.children[center]);
+        }
+        dot.push(String::from("}"));
+        dot.join("\n") + "\n"
+    }
+
+    pub fn save_dot(&self, path: &Path, highlight: Option<(&str, &str)>) -> io::Result<()> {
+        fs::write(path, self.to_dot(highlight))
+    }
+
+    // Built from the leaves up, as dropping a tree this deep still recurses.
+    pub fn to_tree(&self) -> OrbitTree {
+        let mut order = vec![self.root];
+        let mut next = 0;
+        while next < order.len() {
+            order.extend(&self.children[order[next]]);
+            next += 1;
+        }
+        let mut trees: Vec<Option<OrbitTree>> = vec![None; self.names.len()];
+        for object in order.into_iter().rev() {
+            let satellites = self.children[object].iter().map(|x| trees[*x].take().unwrap()).collect();
+            trees[object] = Some(OrbitTree { name: self.names[object].clone(), satellites });
+        }
+        trees[self.root].take().unwrap()
+    }
+
+    // The tree is checked like any other map, so a name used twice is reported.
+    pub fn from_tree(tree: &OrbitTree) -> Result<OrbitMap, Vec<OrbitError>> {
+        let mut orbits = vec![];
+        let mut stack = vec![tree];
+        while let Some(center) = stack.pop() {
+            orbits.extend(center.satellites.iter().map(|x| (center.name.clone(), x.name.clone())));
+            stack.extend(center.satellites.iter().rev());
+        }
+        if orbits.is_empty() {
+            // A lone object has nothing orbiting it, so it has no lines in the text form either.
+            return Err(vec![OrbitError::Empty]);
+        }
+        OrbitMap::from_orbits(orbits)
+    }
+
+    // Compact JSON in the nested form of OrbitTree.
+    pub fn to_json(&self) -> String {
+        self.write_json(false)
+    }
+
+    // Indented JSON, which grows with the square of the depth on real maps.
+    pub fn to_json_pretty(&self) -> String {
+        self.write_json(true)
+    }
+
+    pub fn save_json(&self, path: &Path, pretty: bool) -> io::Result<()> {
+        fs::write(path, self.write_json(pretty))
+    }
+
+    // Writes the same text as serde_json would for OrbitTree::to_value, walking the map
+    // with a stack so deep maps don't overflow.
+    fn write_json(&self, pretty: bool) -> String {
+        enum Step { Object(usize, usize), Separator(usize), Close(usize) }
+        let newline = |json: &mut String, level: usize| if pretty {
+            json.push('\n');
+            json.push_str(&"  ".repeat(level));
+        };
+        let colon = if pretty { ": " } else { ":" };
+
+        let mut json = String::new();
+        let mut stack = vec![Step::Object(self.root, 0)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Object(object, level) => {
+                    json.push('{');
+                    newline(&mut json, level + 1);
+                    json.push_str(&format!("\"name\"{}{}", colon, serde_json::to_string(&self.names[object]).unwrap()));
+                    if self.children[object].is_empty() {
+                        newline(&mut json, level);
+                        json.push('}');
+                        continue;
+                    }
+                    json.push(',');
+                    newline(&mut json, level + 1);
+                    json.push_str(&format!("\"satellites\"{}[", colon));
+                    newline(&mut json, level + 2);
+                    stack.push(Step::Close(level));
+                    for (index, satellite) in self.children[object].iter().enumerate().rev() {
+                        stack.push(Step::Object(*satellite, level + 2));
+                        if index > 0 { stack.push(Step::Separator(level + 2)); }
+                    }
+                }
+                Step::Separator(level) => {
+                    json.push(',');
+                    newline(&mut json, level);
+                }
+                Step::Close(level) => {
+                    newline(&mut json, level + 1);
+                    json.push(']');
+                    newline(&mut json, level);
+                    json.push('}');
+                }
+            }
+        }
+        json
+    }
+
+    // serde_json parses and drops nested values recursively, and its own limit of 128 levels
+    // is lower than real maps need. The nesting is measured first instead, and the value is
+    // handled on a thread with a stack to match.
+    pub fn from_json(json: &str) -> Result<OrbitMap, Vec<OrbitError>> {
+        let nesting = json_nesting(json);
+        if nesting > MAX_JSON_NESTING {
+            return Err(vec![OrbitError::InvalidJson(format!("Nested {} levels deep, the limit is {}", nesting, MAX_JSON_NESTING))]);
+        }
+        thread::scope(|scope| {
+            thread::Builder::new()
+                .stack_size(JSON_STACK + nesting * JSON_STACK_PER_LEVEL)
+                .spawn_scoped(scope, || OrbitMap::from_json_value(json))
+                .expect("Could not start a thread to read the JSON")
+                .join()
+                .unwrap()
+        })
+    }
+
+    fn from_json_value(json: &str) -> Result<OrbitMap, Vec<OrbitError>> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        deserializer.disable_recursion_limit();
+        let value = Value::deserialize(&mut deserializer)
+            .and_then(|value| deserializer.end().map(|_| value))
+            .map_err(|x| vec![OrbitError::InvalidJson(x.to_string())])?;
+
+        let mut orbits = vec![];
+        let mut stack = vec![&value];
+        while let Some(center) = stack.pop() {
+            let name = json_name(center).map_err(|x| vec![OrbitError::InvalidJson(x)])?;
+            match center.get("satellites") {
+                None => {}
+                Some(Value::Array(satellites)) => {
+                    for satellite in satellites {
+                        let object = json_name(satellite).map_err(|x| vec![OrbitError::InvalidJson(x)])?;
+                        orbits.push((name.to_string(), object.to_string()));
+                    }
+                    stack.extend(satellites.iter().rev());
+                }
+                Some(other) => return Err(vec![OrbitError::InvalidJson(format!("Satellites of {} should be a list, found {}", name, other))]),
+            }
+        }
+        if orbits.is_empty() { return Err(vec![OrbitError::Empty]); }
+        OrbitMap::from_orbits(orbits)
+    }
+
+    fn ancestor_at_depth(&self, mut object: usize, depth: usize) -> usize {
+        let mut climb = self.depths[object] - depth;
+        let mut level = 0;
+        while climb > 0 {
+            if climb & 1 == 1 { object = self.ancestors[level][object]; }
+            climb >>= 1;
+            level += 1;
+        }
+        object
+    }
+
+    fn lowest_common_ancestor_index(&self, object1: usize, object2: usize) -> usize {
+        let depth = self.depths[object1].min(self.depths[object2]);
+        let (mut object1, mut object2) = (self.ancestor_at_depth(object1, depth), self.ancestor_at_depth(object2, depth));
+        if object1 == object2 { return object1; }
+
+        for level in (0..self.ancestors.len()).rev() {
+            if self.ancestors[level][object1] != self.ancestors[level][object2] {
+                object1 = self.ancestors[level][object1];
+                object2 = self.ancestors[level][object2];
+            }
+        }
+        self.ancestors[0][object1]
+    }
+}
+
+// Orbits lead both ways, to the object orbited and to its satellites.
+impl Graph for OrbitMap {
+    type Node = String;
+
+    fn neighbors(&self, object: &String) -> Vec<String> {
+        let index = match self.indices.get(object) { Some(x) => *x, None => return vec![] };
+        self.parents[index].iter()
+            .chain(self.children[index].iter())
+            .map(|x| self.names[*x].clone())
+            .collect()
+    }
+}
+
+// Each orbit nests an object inside a list, so this allows maps 2000 orbits deep.
+const MAX_JSON_NESTING: usize = 2 * 2000 + 1;
+// Debug builds use under 2 KiB for each level.
+const JSON_STACK: usize = 1 << 20;
+const JSON_STACK_PER_LEVEL: usize = 8 << 10;
+
+fn json_name(value: &Value) -> Result<&str, String> {
+    value.get("name").and_then(|x| x.as_str()).ok_or_else(|| format!("Expected an object with a name, found {}", value))
+}
+
+// Deepest nesting of lists and objects, ignoring brackets inside strings. Unbalanced
+// brackets are left for the parser to report.
+fn json_nesting(json: &str) -> usize {
+    let (mut nesting, mut deepest) = (0usize, 0);
+    let (mut in_string, mut escaped) = (false, false);
+    for byte in json.bytes() {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' if in_string => escaped = true,
+            b'"' => in_string = !in_string,
+            _ if in_string => {}
+            b'[' | b'{' => {
+                nesting += 1;
+                deepest = deepest.max(nesting);
+            }
+            b']' | b'}' => nesting = nesting.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::orbits::{OrbitError, OrbitMap, OrbitTree};
+    use crate::search::bfs;
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+
+    #[test]
+    fn computes_depths() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        assert_eq!(map.root(), "COM");
+        assert_eq!(map.depth("B"), Some(1));
+        assert_eq!(map.depth("L"), Some(7));
+        assert_eq!(map.depth("COM"), Some(0));
+        assert_eq!(map.depth("X"), None);
+        assert_eq!(map.total_orbits(), 42);
+    }
+
+    #[test]
+    fn answers_ancestor_queries() {
+        let map = OrbitMap::parse(&format!("{}\nK)YOU\nI)SAN", EXAMPLE)).unwrap();
+        assert_eq!(map.lowest_common_ancestor("YOU", "SAN"), Some("D"));
+        assert_eq!(map.lowest_common_ancestor("L", "B"), Some("B"));
+        assert_eq!(map.lowest_common_ancestor("H", "F"), Some("B"));
+        assert_eq!(map.lowest_common_ancestor("COM", "COM"), Some("COM"));
+        assert_eq!(map.distance("L", "B"), Some(6));
+        assert_eq!(map.orbital_transfers("YOU", "SAN"), Some(4));
+        assert_eq!(map.orbital_transfers("COM", "SAN"), None);
+    }
+
+    #[test]
+    fn searches_orbits_as_a_graph() {
+        let map = OrbitMap::parse(&format!("{}\nK)YOU\nI)SAN", EXAMPLE)).unwrap();
+        let result = bfs(&map, String::from("YOU"));
+        assert_eq!(result.path_to(&String::from("SAN")).unwrap(), map.transfer_path("YOU", "SAN").unwrap());
+        for object in map.objects() {
+            assert_eq!(result.distance(&object.to_string()), map.distance("YOU", object).map(|x| x as u64));
+        }
+    }
+
+    #[test]
+    fn matches_walking_up_on_a_long_chain() {
+        let orbits = (0..1000).map(|x| format!("{}){}", x, x + 1))
+            .chain((0..1000).step_by(7).map(|x| format!("{})b{}", x, x)))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let map = OrbitMap::parse(&orbits).unwrap();
+        assert_eq!(map.depth("1000"), Some(1000));
+        assert_eq!(map.lowest_common_ancestor("b700", "b350"), Some("350"));
+        assert_eq!(map.distance("b700", "b357"), Some(345));
+        assert_eq!(map.distance("999", "b0"), Some(1000));
+    }
+
+    #[test]
+    fn reports_invalid_maps() {
+        assert_eq!(OrbitMap::parse("COM)B\nB-C\n\nB)").err(), Some(vec![
+            OrbitError::InvalidLine { line: 2, text: String::from("B-C") },
+            OrbitError::InvalidLine { line: 4, text: String::from("B)") },
+        ]));
+        assert_eq!(OrbitMap::parse("").err(), Some(vec![OrbitError::Empty]));
+        assert_eq!(OrbitMap::parse("COM)A\nCOM)B\nA)B\nCOM)B").err(), Some(vec![
+            OrbitError::DuplicateParent { object: String::from("B"), first: String::from("COM"), second: String::from("A") },
+        ]));
+        assert_eq!(OrbitMap::parse("COM)B\nX)Y").err(), Some(vec![
+            OrbitError::MultipleRoots(vec![String::from("COM"), String::from("X")]),
+        ]));
+        assert_eq!(OrbitMap::parse("COM)B\nD)C\nC)E\nE)D\nE)F").err(), Some(vec![
+            OrbitError::Cycle(vec![String::from("C"), String::from("D"), String::from("E")]),
+            OrbitError::Unreachable(vec![String::from("C"), String::from("D"), String::from("E"), String::from("F")]),
+        ]));
+    }
+
+    #[test]
+    fn exports_dot_with_transfer_path() {
+        let map = OrbitMap::parse(&format!("{}\nK)YOU\nI)SAN", EXAMPLE)).unwrap();
+        assert_eq!(map.transfer_path("YOU", "SAN").unwrap(), vec!["YOU", "K", "J", "E", "D", "I", "SAN"]);
+        assert_eq!(map.transfer_path("L", "B").unwrap(), vec!["L", "K", "J", "E", "D", "C", "B"]);
+
+        let dot = map.to_dot(Some(("YOU", "SAN")));
+        assert!(dot.starts_with("digraph orbits {\n"));
+        assert!(dot.contains("    \"YOU\" [style=filled, fillcolor=gold];\n"));
+        assert!(dot.contains("    \"D\" -> \"E\" [color=red, penwidth=3];\n"));
+        assert!(dot.contains("    \"D\" -> \"I\" [color=red, penwidth=3];\n"));
+        assert!(dot.contains("    \"C\" -> \"D\";\n"));
+        assert_eq!(dot.matches("->").count(), 13);
+        assert_eq!(map.to_dot(None).matches("red").count(), 0);
+    }
+
+    #[test]
+    fn round_trips_json() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        let json = map.to_json();
+        let tree = OrbitTree::from_value(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(tree.name, "COM");
+        assert_eq!(tree.satellites.len(), 1);
+        assert_eq!(tree.satellites[0].satellites.iter().map(|x| x.name.as_str()).collect::<Vec<&str>>(), vec!["C", "G"]);
+
+        let read = OrbitMap::from_json(&json).unwrap();
+        assert_eq!(read.to_json(), json);
+        assert_eq!(read.total_orbits(), 42);
+
+        assert_eq!(json, serde_json::to_string(&map.to_tree().to_value()).unwrap());
+        assert_eq!(map.to_json_pretty(), serde_json::to_string_pretty(&map.to_tree().to_value()).unwrap());
+        assert_eq!(OrbitMap::from_json(&map.to_json_pretty()).unwrap().to_json(), json);
+    }
+
+    #[test]
+    fn round_trips_deep_json() {
+        let chain = |depth: usize| (0..depth).map(|x| format!("{}){}", x, x + 1)).collect::<Vec<String>>().join("\n");
+        let json = OrbitMap::parse(&chain(2000)).unwrap().to_json();
+        assert_eq!(OrbitMap::from_json(&json).unwrap().depth("2000"), Some(2000));
+
+        let json = OrbitMap::parse(&chain(100_000)).unwrap().to_json();
+        assert!(json.starts_with(r#"{"name":"0","satellites":[{"name":"1","satellites":[{"#));
+        assert_eq!(OrbitMap::from_json(&json).err(), Some(vec![
+            OrbitError::InvalidJson(String::from("Nested 200001 levels deep, the limit is 4001")),
+        ]));
+        assert!(matches!(OrbitMap::from_json(&format!("{}]", "[".repeat(100_000))).err().unwrap()[0], OrbitError::InvalidJson(_)));
+    }
+
+    #[test]
+    fn reports_invalid_json() {
+        assert!(matches!(OrbitMap::from_json("{\"name\": \"COM\"").err().unwrap()[0], OrbitError::InvalidJson(_)));
+        assert_eq!(OrbitMap::from_json("{\"name\": \"COM\"}").err(), Some(vec![OrbitError::Empty]));
+        assert_eq!(OrbitMap::from_json("{\"name\": \"COM\", \"satellites\": {}}").err(), Some(vec![
+            OrbitError::InvalidJson(String::from("Satellites of COM should be a list, found {}")),
+        ]));
+        assert!(matches!(OrbitMap::from_json("[1]").err().unwrap()[0], OrbitError::InvalidJson(_)));
+        assert_eq!(OrbitMap::from_json(r#"{"name": "COM", "satellites": [{"name": "[[["}, 4]}"#).err(), Some(vec![
+            OrbitError::InvalidJson(String::from("Expected an object with a name, found 4")),
+        ]));
+        let json = r#"{"name": "COM", "satellites": [{"name": "A", "satellites": [{"name": "B"}]}, {"name": "B"}]}"#;
+        assert_eq!(OrbitMap::from_json(json).err(), Some(vec![
+            OrbitError::DuplicateParent { object: String::from("B"), first: String::from("COM"), second: String::from("A") },
+        ]));
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::path::Path;
+use image::{ImageResult, RgbImage};
+use crate::frames::{FrameRecorder, Palette};
+use crate::maze::{Maze, Position, PositionStatus};
+use crate::search::bfs_from;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FillState {
+    Wall,
+    Empty,
+    Filled,
+    // Filled during the minute shown.
+    Front,
+}
+
+// How long oxygen took to get somewhere, for the heatmap.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Heat {
+    Wall,
+    Minute(u64),
+    Unreached,
+}
+
+// Oxygen spreading from its sources into the open positions next to it once a minute.
+pub struct FloodFill<'a> {
+    maze: &'a Maze,
+    fill_times: HashMap<Position, u64>,
+    // fronts[minute] are the positions filled during that minute, the sources at minute 0.
+    fronts: Vec<Vec<Position>>,
+}
+
+impl<'a> FloodFill<'a> {
+    // Sources that aren't open are left out.
+    pub fn new(maze: &'a Maze, sources: Vec<Position>) -> FloodFill<'a> {
+        let sources = sources.into_iter().filter(|x| maze.status(*x).is_open()).collect();
+        let result = bfs_from(maze, sources);
+        let mut fronts: Vec<Vec<Position>> = vec![];
+        for position in &result.order {
+            let minute = result.distance(position).unwrap() as usize;
+            if fronts.len() <= minute { fronts.push(vec![]); }
+            fronts[minute].push(*position);
+        }
+        FloodFill { maze, fill_times: result.distances().clone(), fronts }
+    }
+
+    pub fn from_oxygen_system(maze: &'a Maze) -> FloodFill<'a> {
+        FloodFill::new(maze, maze.oxygen_system().into_iter().collect())
+    }
+
+    // Minutes until the last position is filled, none without sources.
+    pub fn minutes(&self) -> Option<u64> {
+        self.fronts.len().checked_sub(1).map(|x| x as u64)
+    }
+
+    pub fn fill_time(&self, position: Position) -> Option<u64> {
+        self.fill_times.get(&position).copied()
+    }
+
+    pub fn fill_times(&self) -> &HashMap<Position, u64> {
+        &self.fill_times
+    }
+
+    pub fn front(&self, minute: u64) -> &[Position] {
+        self.fronts.get(minute as usize).map_or(&[], |x| &x[..])
+    }
+
+    pub fn fronts(&self) -> &[Vec<Position>] {
+        &self.fronts
+    }
+
+    // Open positions walled off from every source, by row.
+    pub fn unreached(&self) -> Vec<Position> {
+        let mut unreached: Vec<Position> = self.maze.blocks().values()
+            .filter(|x| x.status.is_open() && !self.fill_times.contains_key(&x.position))
+            .map(|x| x.position)
+            .collect();
+        unreached.sort_by_key(|x| (x.1, x.0));
+        unreached
+    }
+
+    // The explored maze at the end of a minute.
+    pub fn snapshot(&self, minute: u64) -> HashMap<Position, FillState> {
+        self.maze.blocks().values()
+            .filter(|x| x.status != PositionStatus::NotVisited)
+            .map(|x| {
+                let state = match (x.status, self.fill_time(x.position)) {
+                    (PositionStatus::Wall, _) => FillState::Wall,
+                    (_, Some(time)) if time == minute => FillState::Front,
+                    (_, Some(time)) if time < minute => FillState::Filled,
+                    _ => FillState::Empty,
+                };
+                (x.position, state)
+            })
+            .collect()
+    }
+
+    // One frame per minute, from the sources alone until everything reachable is filled.
+    pub fn record(&self, recorder: &mut FrameRecorder<FillState>) {
+        for minute in 0..self.fronts.len() as u64 {
+            recorder.record(self.snapshot(minute));
+        }
+    }
+
+    pub fn palette() -> Palette<FillState> {
+        Palette::new([0, 0, 0])
+            .with(FillState::Wall, [128, 128, 128])
+            .with(FillState::Empty, [255, 255, 255])
+            .with(FillState::Filled, [0, 128, 255])
+            .with(FillState::Front, [0, 255, 255])
+    }
+
+    // Positions filled early are yellow and fade to red the later they're filled.
+    pub fn heatmap(&self, scale: u32) -> RgbImage {
+        let minutes = self.minutes().unwrap_or(0);
+        let palette = (0..=minutes).fold(
+            Palette::new([0, 0, 0])
+                .with(Heat::Wall, [128, 128, 128])
+                .with(Heat::Unreached, [0, 0, 160]),
+            |palette, minute| palette.with(Heat::Minute(minute), [255, (255 - 255 * minute / minutes.max(1)) as u8, 0]),
+        );
+
+        let mut recorder = FrameRecorder::new(palette).scale(scale);
+        recorder.record(self.maze.blocks().values()
+            .filter(|x| x.status != PositionStatus::NotVisited)
+            .map(|x| {
+                let heat = match (x.status, self.fill_time(x.position)) {
+                    (PositionStatus::Wall, _) => Heat::Wall,
+                    (_, Some(time)) => Heat::Minute(time),
+                    (_, None) => Heat::Unreached,
+                };
+                (x.position, heat)
+            }));
+        recorder.render().remove(0)
+    }
+
+    pub fn save_heatmap(&self, path: &Path, scale: u32) -> ImageResult<()> {
+        self.heatmap(scale).save(path).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::{Maze, MazeBlock, Position, PositionStatus};
+    use crate::oxygen::{FillState, FloodFill};
+    use crate::frames::FrameRecorder;
+    use image::Rgb;
+
+    // A corridor from (0, 0) to (4, 0) with walls above and below, and a pocket at (6, 0)
+    // behind a wall.
+    fn maze() -> Maze {
+        let mut blocks = vec![];
+        let mut block = |position: Position, status: PositionStatus| blocks.push((position, MazeBlock::from(position, status, None, None)));
+        for x in 0..7 {
+            block((x, -1), PositionStatus::Wall);
+            block((x, 1), PositionStatus::Wall);
+        }
+        block((0, 0), PositionStatus::Home);
+        (1..4).for_each(|x| block((x, 0), PositionStatus::Visited));
+        block((4, 0), PositionStatus::LocationOfOxygenSystem);
+        block((5, 0), PositionStatus::Wall);
+        block((6, 0), PositionStatus::Visited);
+        Maze::new(blocks.into_iter().collect())
+    }
+
+    #[test]
+    fn fills_from_the_oxygen_system() {
+        let maze = maze();
+        let fill = FloodFill::from_oxygen_system(&maze);
+        assert_eq!(fill.minutes(), Some(4));
+        assert_eq!(fill.front(0), &[(4, 0)]);
+        assert_eq!(fill.front(3), &[(1, 0)]);
+        assert!(fill.front(5).is_empty());
+        assert_eq!(fill.fill_time((0, 0)), Some(4));
+        assert_eq!(fill.fill_time((5, 0)), None);
+        assert_eq!(fill.unreached(), vec![(6, 0)]);
+
+        let snapshot = fill.snapshot(2);
+        assert_eq!(snapshot[&(4, 0)], FillState::Filled);
+        assert_eq!(snapshot[&(2, 0)], FillState::Front);
+        assert_eq!(snapshot[&(1, 0)], FillState::Empty);
+        assert_eq!(snapshot[&(5, 0)], FillState::Wall);
+    }
+
+    #[test]
+    fn fills_from_several_sources() {
+        let maze = maze();
+        let fill = FloodFill::new(&maze, vec![(0, 0), (4, 0), (6, 0), (5, 0)]);
+        assert_eq!(fill.minutes(), Some(2));
+        assert_eq!(fill.front(0), &[(0, 0), (4, 0), (6, 0)]);
+        assert_eq!(fill.front(2), &[(2, 0)]);
+        assert!(fill.unreached().is_empty());
+        assert_eq!(FloodFill::new(&maze, vec![]).minutes(), None);
+    }
+
+    #[test]
+    fn records_minutes_and_heatmap() {
+        let maze = maze();
+        let fill = FloodFill::from_oxygen_system(&maze);
+        let mut recorder = FrameRecorder::new(FloodFill::palette());
+        fill.record(&mut recorder);
+        assert_eq!(recorder.len(), 5);
+
+        let heatmap = fill.heatmap(2);
+        assert_eq!(heatmap.dimensions(), (14, 6));
+        assert_eq!(*heatmap.get_pixel(8, 2), Rgb([255, 255, 0]));
+        assert_eq!(*heatmap.get_pixel(0, 2), Rgb([255, 0, 0]));
+        assert_eq!(*heatmap.get_pixel(12, 2), Rgb([0, 0, 160]));
+        assert_eq!(*heatmap.get_pixel(10, 2), Rgb([128, 128, 128]));
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    // Number of digits, without leading zeros.
+    Length(usize),
+    NonDecreasing,
+    // Two adjacent digits are the same.
+    AdjacentPair,
+    // Two adjacent digits are the same and not part of a larger group.
+    ExactPair,
+    // Every digit is one of these, indexed by digit.
+    Digits([bool; 10]),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleError {
+    UnknownRule(String),
+    InvalidArgument(String),
+    InvalidDigit(u8),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::UnknownRule(rule) => write!(f, "Unknown rule {:?}", rule),
+            RuleError::InvalidArgument(rule) => write!(f, "Invalid argument in {:?}", rule),
+            RuleError::InvalidDigit(digit) => write!(f, "{} is not a digit", digit),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// Rules a password has to meet, e.g. the ones from day 4 part 2:
+// `PasswordRules::parse("length 6, non-decreasing, exact-pair")`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PasswordRules {
+    rules: Vec<Rule>,
+}
+
+// Where the digit DP is once some digits are placed. Runs longer than two are all alike.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+struct State {
+    last_digit: Option<u8>,
+    run: u8,
+    has_adjacent_pair: bool,
+    has_exact_pair: bool,
+}
+
+impl State {
+    const START: State = State { last_digit: None, run: 0, has_adjacent_pair: false, has_exact_pair: false };
+}
+
+impl PasswordRules {
+    pub fn new() -> PasswordRules {
+        PasswordRules { rules: vec![] }
+    }
+
+    pub fn rule(mut self, rule: Rule) -> PasswordRules {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn length(self, length: usize) -> PasswordRules {
+        self.rule(Rule::Length(length))
+    }
+
+    pub fn non_decreasing(self) -> PasswordRules {
+        self.rule(Rule::NonDecreasing)
+    }
+
+    pub fn adjacent_pair(self) -> PasswordRules {
+        self.rule(Rule::AdjacentPair)
+    }
+
+    pub fn exact_pair(self) -> PasswordRules {
+        self.rule(Rule::ExactPair)
+    }
+
+    pub fn only_digits(self, digits: &[u8]) -> Result<PasswordRules, RuleError> {
+        let mut allowed = [false; 10];
+        for digit in digits {
+            *allowed.get_mut(*digit as usize).ok_or(RuleError::InvalidDigit(*digit))? = true;
+        }
+        Ok(self.rule(Rule::Digits(allowed)))
+    }
+
+    pub fn without_digits(self, digits: &[u8]) -> Result<PasswordRules, RuleError> {
+        let mut allowed = [true; 10];
+        for digit in digits {
+            *allowed.get_mut(*digit as usize).ok_or(RuleError::InvalidDigit(*digit))? = false;
+        }
+        Ok(self.rule(Rule::Digits(allowed)))
+    }
+
+    // Comma separated rules: `length <n>`, `non-decreasing`, `adjacent-pair`, `exact-pair`,
+    // `digits <digits>` and `no-digits <digits>`, where digits are like `13579` or `2-7`.
+    pub fn parse(rules: &str) -> Result<PasswordRules, RuleError> {
+        rules.split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .try_fold(PasswordRules::new(), |rules, rule| {
+                let mut words = rule.split_whitespace();
+                let name = words.next().unwrap();
+                let argument = words.next();
+                let invalid_argument = || RuleError::InvalidArgument(rule.to_string());
+                if words.next().is_some() { return Err(invalid_argument()); }
+
+                let digits = || argument.and_then(parse_digits).ok_or_else(invalid_argument);
+                let no_argument = |rules: PasswordRules| argument.map_or(Ok(rules), |_| Err(invalid_argument()));
+
+                match name {
+                    "length" => Ok(rules.length(argument.and_then(|x| x.parse().ok()).ok_or_else(invalid_argument)?)),
+                    "non-decreasing" => no_argument(rules.non_decreasing()),
+                    "adjacent-pair" => no_argument(rules.adjacent_pair()),
+                    "exact-pair" => no_argument(rules.exact_pair()),
+                    "digits" => rules.only_digits(&digits()?),
+                    "no-digits" => rules.without_digits(&digits()?),
+                    _ => Err(RuleError::UnknownRule(rule.to_string()))
+                }
+            })
+    }
+
+    pub fn is_valid(&self, password: u64) -> bool {
+        let digits = password.to_string().bytes().map(|x| x - b'0').collect::<Vec<u8>>();
+        let runs = digits.iter()
+            .fold(Vec::new(), |mut runs: Vec<(u8, usize)>, digit| {
+                match runs.last_mut() {
+                    Some((last, count)) if last == digit => *count += 1,
+                    _ => runs.push((*digit, 1))
+                }
+                runs
+            });
+
+        self.rules.iter().all(|rule| match rule {
+            Rule::Length(length) => digits.len() == *length,
+            Rule::NonDecreasing => digits.windows(2).all(|x| x[0] <= x[1]),
+            Rule::AdjacentPair => runs.iter().any(|x| x.1 >= 2),
+            Rule::ExactPair => runs.iter().any(|x| x.1 == 2),
+            Rule::Digits(allowed) => digits.iter().all(|x| allowed[*x as usize]),
+        })
+    }
+
+    // Counts valid passwords in the range with a DP over digits, so the size of the range
+    // doesn't matter, only the number of digits. Saturates at u64::MAX, which only the whole
+    // u64 range without rules gets past.
+    pub fn count(&self, range: RangeInclusive<u64>) -> u64 {
+        if range.start() > range.end() { return 0; }
+        let count = self.count_up_to(*range.end()) - self.rank_before(*range.start());
+        count.min(u64::MAX as u128) as u64
+    }
+
+    // All valid passwords in the range, smallest first, found one at a time.
+    pub fn passwords(&self, range: RangeInclusive<u64>) -> impl Iterator<Item = u64> + '_ {
+        let end = *range.end();
+        let ranks = if range.start() > range.end() {
+            None
+        } else {
+            Some(self.rank_before(*range.start()) + 1..=self.count_up_to(end))
+        };
+        ranks.into_iter().flatten().map(move |rank| self.unrank(end, rank).unwrap())
+    }
+
+    // The valid password at `index` in the range, counting from 0 in increasing order.
+    pub fn nth(&self, range: RangeInclusive<u64>, index: u64) -> Option<u64> {
+        if range.start() > range.end() { return None; }
+        self.unrank(*range.end(), self.rank_before(*range.start()) + index as u128 + 1)
+    }
+
+    // `amount` valid passwords picked uniformly from the range, repeats allowed. The same
+    // seed gives the same picks.
+    pub fn sample(&self, range: RangeInclusive<u64>, amount: usize, seed: u64) -> Vec<u64> {
+        let count = self.count(range.clone());
+        if count == 0 { return vec![]; }
+
+        let mut state = seed;
+        (0..amount).map(|_| {
+            // splitmix64
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            self.nth(range.clone(), (z ^ (z >> 31)) % count).unwrap()
+        }).collect()
+    }
+
+    // Number of valid passwords below `password`.
+    fn rank_before(&self, password: u64) -> u128 {
+        if password == 0 { 0 } else { self.count_up_to(password - 1) }
+    }
+
+    // Valid passwords in 0..=limit.
+    fn count_up_to(&self, limit: u64) -> u128 {
+        let mut memo = HashMap::new();
+        PasswordRules::bounds(limit)
+            .map(|bound| self.count_digits(&bound, 0, true, State::START, &mut memo))
+            .sum()
+    }
+
+    // The valid password with `rank` valid passwords up to and including it, counting from 1,
+    // if it isn't past `limit`. Goes through the lengths and then the digits, skipping over
+    // as many passwords as each choice holds.
+    fn unrank(&self, limit: u64, mut rank: u128) -> Option<u64> {
+        let mut memo = HashMap::new();
+        for bound in PasswordRules::bounds(limit) {
+            let count = self.count_digits(&bound, 0, true, State::START, &mut memo);
+            if rank > count {
+                rank -= count;
+                continue;
+            }
+
+            let (mut password, mut state, mut is_tight) = (0, State::START, true);
+            for position in 0..bound.len() {
+                for digit in self.candidates(&bound, position, is_tight, state) {
+                    let next = PasswordRules::place(state, digit);
+                    let next_is_tight = is_tight && digit == bound[position];
+                    let count = self.count_digits(&bound, position + 1, next_is_tight, next, &mut memo);
+                    if rank > count {
+                        rank -= count;
+                    } else {
+                        password = password * 10 + digit as u64;
+                        state = next;
+                        is_tight = next_is_tight;
+                        break;
+                    }
+                }
+            }
+            return Some(password);
+        }
+        None
+    }
+
+    // The largest number of each length up to the length of `limit`, ending with `limit`.
+    fn bounds(limit: u64) -> impl Iterator<Item = Vec<u8>> {
+        let limit_digits = limit.to_string().bytes().map(|x| x - b'0').collect::<Vec<u8>>();
+        (1..=limit_digits.len()).map(move |length| {
+            if length == limit_digits.len() { limit_digits.clone() } else { vec![9; length] }
+        })
+    }
+
+    // Digits that can go at `position` and still meet the rules.
+    fn candidates(&self, bound: &[u8], position: usize, is_tight: bool, state: State) -> impl Iterator<Item = u8> + '_ {
+        let is_wrong_length = self.rules.iter().any(|x| matches!(x, Rule::Length(l) if *l != bound.len()));
+        // Only a single digit password may start with 0.
+        let lowest = if position == 0 && bound.len() > 1 { 1 } else { 0 };
+        let highest = if is_wrong_length { None } else if is_tight { Some(bound[position]) } else { Some(9) };
+
+        highest.into_iter()
+            .flat_map(move |highest| lowest..=highest)
+            .filter(move |digit| self.rules.iter().all(|rule| match rule {
+                Rule::NonDecreasing => state.last_digit.is_none_or(|last| last <= *digit),
+                Rule::Digits(allowed) => allowed[*digit as usize],
+                _ => true
+            }))
+    }
+
+    // Ways to finish a password from `position`, memoized on the digits left once the
+    // bound no longer matters.
+    fn count_digits(&self, bound: &[u8], position: usize, is_tight: bool, state: State,
+                    memo: &mut HashMap<(usize, State), u128>) -> u128 {
+        if position == bound.len() {
+            return if self.accepts(&state) { 1 } else { 0 };
+        }
+        let key = (bound.len() - position, state);
+        if !is_tight {
+            if let Some(count) = memo.get(&key) { return *count; }
+        }
+
+        let count = self.candidates(bound, position, is_tight, state)
+            .map(|digit| {
+                let next = PasswordRules::place(state, digit);
+                self.count_digits(bound, position + 1, is_tight && digit == bound[position], next, memo)
+            })
+            .sum();
+
+        if !is_tight { memo.insert(key, count); }
+        count
+    }
+
+    fn place(state: State, digit: u8) -> State {
+        if state.last_digit == Some(digit) {
+            State {
+                run: (state.run + 1).min(3),
+                has_adjacent_pair: true,
+                ..state
+            }
+        } else {
+            State {
+                last_digit: Some(digit),
+                run: 1,
+                has_exact_pair: state.has_exact_pair || state.run == 2,
+                ..state
+            }
+        }
+    }
+
+    fn accepts(&self, state: &State) -> bool {
+        self.rules.iter().all(|rule| match rule {
+            Rule::AdjacentPair => state.has_adjacent_pair,
+            Rule::ExactPair => state.has_exact_pair || state.run == 2,
+            _ => true
+        })
+    }
+}
+
+fn parse_digits(digits: &str) -> Option<Vec<u8>> {
+    let digit = |x: &str| x.parse::<u8>().ok().filter(|x| *x < 10);
+    match digits.split_once('-') {
+        Some((low, high)) => Some((digit(low)?..=digit(high)?).collect()),
+        None => digits.chars().map(|x| x.to_digit(10).map(|x| x as u8)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::passwords::{PasswordRules, Rule, RuleError};
+    use std::ops::RangeInclusive;
+
+    fn day_four() -> PasswordRules {
+        PasswordRules::new().length(6).non_decreasing().adjacent_pair()
+    }
+
+    #[test]
+    fn checks_rules() {
+        assert!(day_four().is_valid(111111));
+        assert!(!day_four().is_valid(223450));
+        assert!(!day_four().is_valid(123789));
+        assert!(!day_four().is_valid(12378));
+
+        let exact = day_four().exact_pair();
+        assert!(exact.is_valid(112233));
+        assert!(!exact.is_valid(123444));
+        assert!(exact.is_valid(111122));
+    }
+
+    #[test]
+    fn counts_like_brute_force() {
+        let rule_sets = [
+            day_four(),
+            day_four().exact_pair(),
+            PasswordRules::new().exact_pair().without_digits(&[3]).unwrap(),
+            PasswordRules::new().non_decreasing().only_digits(&[0, 2, 4, 6, 8]).unwrap(),
+            PasswordRules::new().length(4).adjacent_pair(),
+            PasswordRules::new(),
+        ];
+        for rules in rule_sets.iter() {
+            for range in [0..=0, 0..=99_999, 7..=12_345, 100_000..=123_456, RangeInclusive::new(5, 4)] {
+                let expected = range.clone().filter(|x| rules.is_valid(*x)).count() as u64;
+                assert_eq!(rules.count(range.clone()), expected, "{:?} in {:?}", rules, range);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_puzzle_range() {
+        assert_eq!(day_four().count(357253..=892942), 530);
+        assert_eq!(day_four().exact_pair().count(357253..=892942), 324);
+        assert_eq!(PasswordRules::new().non_decreasing().exact_pair().count(0..=u64::MAX), 7_174_245);
+    }
+
+    #[test]
+    fn enumerates_and_samples() {
+        let rules = day_four().exact_pair();
+        let passwords = rules.passwords(357253..=892942).collect::<Vec<u64>>();
+        assert_eq!(passwords.len(), 324);
+        assert_eq!(passwords, (357253..=892942).filter(|x| rules.is_valid(*x)).collect::<Vec<u64>>());
+        assert_eq!(rules.nth(357253..=892942, 0), Some(passwords[0]));
+        assert_eq!(rules.nth(357253..=892942, 323), Some(passwords[323]));
+        assert_eq!(rules.nth(357253..=892942, 324), None);
+
+        let sample = rules.sample(357253..=892942, 20, 42);
+        assert_eq!(sample.len(), 20);
+        assert!(sample.iter().all(|x| passwords.contains(x)));
+        assert_eq!(sample, rules.sample(357253..=892942, 20, 42));
+        assert_eq!(PasswordRules::new().length(3).count(0..=99), 0);
+        assert_eq!(PasswordRules::new().length(3).sample(0..=99, 5, 1), Vec::<u64>::new());
+        assert_eq!(PasswordRules::new().passwords(u64::MAX - 1..=u64::MAX).count(), 2);
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!(PasswordRules::parse("length 6, non-decreasing, adjacent-pair"), Ok(day_four()));
+        assert_eq!(PasswordRules::parse("exact-pair, digits 2-4, no-digits 3"),
+                   PasswordRules::new().exact_pair().only_digits(&[2, 3, 4]).and_then(|x| x.without_digits(&[3])));
+        assert_eq!(PasswordRules::parse("digits 13579").map(|x| x.rules),
+                   Ok(vec![Rule::Digits([false, true, false, true, false, true, false, true, false, true])]));
+
+        assert_eq!(PasswordRules::parse("length six"), Err(RuleError::InvalidArgument(String::from("length six"))));
+        assert_eq!(PasswordRules::parse("exact-pair 2"), Err(RuleError::InvalidArgument(String::from("exact-pair 2"))));
+        assert_eq!(PasswordRules::parse("digits 5-a"), Err(RuleError::InvalidArgument(String::from("digits 5-a"))));
+        assert_eq!(PasswordRules::parse("digits 0-10"), Err(RuleError::InvalidArgument(String::from("digits 0-10"))));
+        assert_eq!(PasswordRules::parse("no-digits 12-3"), Err(RuleError::InvalidArgument(String::from("no-digits 12-3"))));
+        assert_eq!(PasswordRules::parse("palindrome"), Err(RuleError::UnknownRule(String::from("palindrome"))));
+    }
+
+    #[test]
+    fn rejects_digits_above_nine() {
+        assert_eq!(PasswordRules::new().only_digits(&[1, 12]), Err(RuleError::InvalidDigit(12)));
+        assert_eq!(PasswordRules::new().without_digits(&[10]), Err(RuleError::InvalidDigit(10)));
+        assert!(PasswordRules::new().only_digits(&[0, 9]).is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+// Anything that can list where a node leads. Moving along an edge costs 1 unless the graph
+// says otherwise, which only Dijkstra and A* look at.
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    fn neighbors(&self, node: &Self::Node) -> Vec<Self::Node>;
+
+    fn cost(&self, _from: &Self::Node, _to: &Self::Node) -> u64 {
+        1
+    }
+}
+
+// An adjacency list is a graph as it is.
+impl<N: Clone + Eq + Hash> Graph for HashMap<N, Vec<N>> {
+    type Node = N;
+
+    fn neighbors(&self, node: &N) -> Vec<N> {
+        self.get(node).cloned().unwrap_or_default()
+    }
+}
+
+// What a search reached, how far away each node is and how it got there. Nodes are in
+// `order` as they were settled, so for BFS that's by distance.
+#[derive(Debug, Clone)]
+pub struct SearchResult<N: Clone + Eq + Hash> {
+    pub order: Vec<N>,
+    distances: HashMap<N, u64>,
+    previous: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash> SearchResult<N> {
+    fn new() -> SearchResult<N> {
+        SearchResult { order: vec![], distances: HashMap::new(), previous: HashMap::new() }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<u64> {
+        self.distances.get(node).copied()
+    }
+
+    pub fn distances(&self) -> &HashMap<N, u64> {
+        &self.distances
+    }
+
+    // Distance to the farthest node reached.
+    pub fn max_distance(&self) -> Option<u64> {
+        self.distances.values().max().copied()
+    }
+
+    // From the start that reached the node to the node, both included.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        self.distances.get(node)?;
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.previous.get(&path[path.len() - 1]) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+pub fn bfs<G: Graph>(graph: &G, start: G::Node) -> SearchResult<G::Node> {
+    bfs_from(graph, vec![start])
+}
+
+// Breadth first from every start at once, ignoring edge costs.
+pub fn bfs_from<G: Graph>(graph: &G, starts: Vec<G::Node>) -> SearchResult<G::Node> {
+    let mut result = SearchResult::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if result.distances.insert(start.clone(), 0).is_none() { queue.push_back(start); }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let distance = result.distances[&node];
+        for neighbor in graph.neighbors(&node) {
+            if result.distances.contains_key(&neighbor) { continue; }
+            result.distances.insert(neighbor.clone(), distance + 1);
+            result.previous.insert(neighbor.clone(), node.clone());
+            queue.push_back(neighbor);
+        }
+        result.order.push(node);
+    }
+    result
+}
+
+pub fn dijkstra<G: Graph>(graph: &G, start: G::Node) -> SearchResult<G::Node> {
+    best_first(graph, vec![start], None, |_| 0)
+}
+
+// Stops once the goal is settled. The heuristic must never overestimate the remaining cost,
+// and if it also never drops by more than an edge's cost along that edge the path found is
+// the cheapest.
+pub fn astar<G: Graph, H: Fn(&G::Node) -> u64>(graph: &G, start: G::Node, goal: &G::Node, heuristic: H) -> SearchResult<G::Node> {
+    best_first(graph, vec![start], Some(goal), heuristic)
+}
+
+fn best_first<G: Graph, H: Fn(&G::Node) -> u64>(
+    graph: &G,
+    starts: Vec<G::Node>,
+    goal: Option<&G::Node>,
+    heuristic: H,
+) -> SearchResult<G::Node> {
+    let mut result = SearchResult::new();
+    // The heap holds indices into `nodes` so nodes don't need to be ordered.
+    let mut nodes: Vec<G::Node> = vec![];
+    let mut heap = BinaryHeap::new();
+    let mut settled: HashSet<G::Node> = HashSet::new();
+    for start in starts {
+        result.distances.insert(start.clone(), 0);
+        heap.push(Reverse((heuristic(&start), nodes.len())));
+        nodes.push(start);
+    }
+
+    while let Some(Reverse((_, index))) = heap.pop() {
+        let node = nodes[index].clone();
+        if !settled.insert(node.clone()) { continue; }
+        let distance = result.distances[&node];
+        result.order.push(node.clone());
+        if goal == Some(&node) { break; }
+
+        for neighbor in graph.neighbors(&node) {
+            let candidate = distance + graph.cost(&node, &neighbor);
+            if result.distances.get(&neighbor).is_some_and(|x| *x <= candidate) { continue; }
+            result.distances.insert(neighbor.clone(), candidate);
+            result.previous.insert(neighbor.clone(), node.clone());
+            heap.push(Reverse((candidate + heuristic(&neighbor), nodes.len())));
+            nodes.push(neighbor);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{astar, bfs, bfs_from, dijkstra, Graph};
+    use std::collections::HashMap;
+
+    // Open cells of a small grid, moving costs the digit of the cell entered.
+    struct Grid(Vec<&'static str>);
+
+    impl Graph for Grid {
+        type Node = (i64, i64);
+
+        fn neighbors(&self, node: &(i64, i64)) -> Vec<(i64, i64)> {
+            [(0, -1), (1, 0), (0, 1), (-1, 0)].iter()
+                .map(|(dx, dy)| (node.0 + dx, node.1 + dy))
+                .filter(|(x, y)| self.cell(*x, *y).is_some_and(|x| x != '#'))
+                .collect()
+        }
+
+        fn cost(&self, _from: &(i64, i64), to: &(i64, i64)) -> u64 {
+            self.cell(to.0, to.1).unwrap().to_digit(10).unwrap() as u64
+        }
+    }
+
+    impl Grid {
+        fn cell(&self, x: i64, y: i64) -> Option<char> {
+            if x < 0 || y < 0 { return None; }
+            self.0.get(y as usize)?.chars().nth(x as usize)
+        }
+    }
+
+    fn grid() -> Grid {
+        Grid(vec![
+            "1191",
+            "1#91",
+            "1#11",
+            "1111",
+        ])
+    }
+
+    #[test]
+    fn searches_breadth_first() {
+        let graph = vec![(1, vec![2, 3]), (2, vec![4]), (3, vec![4]), (4, vec![5]), (6, vec![1])]
+            .into_iter()
+            .collect::<HashMap<i32, Vec<i32>>>();
+        let result = bfs(&graph, 1);
+        assert_eq!(result.order, vec![1, 2, 3, 4, 5]);
+        assert_eq!(result.distance(&5), Some(3));
+        assert_eq!(result.distance(&6), None);
+        assert_eq!(result.path_to(&5), Some(vec![1, 2, 4, 5]));
+        assert_eq!(result.path_to(&1), Some(vec![1]));
+        assert_eq!(result.max_distance(), Some(3));
+
+        let result = bfs_from(&graph, vec![6, 4]);
+        assert_eq!(result.distance(&5), Some(1));
+        assert_eq!(result.distance(&3), Some(2));
+        assert_eq!(result.path_to(&3), Some(vec![6, 1, 3]));
+    }
+
+    #[test]
+    fn finds_cheapest_paths() {
+        let result = dijkstra(&grid(), (0, 0));
+        assert_eq!(result.distance(&(3, 0)), Some(9));
+        assert_eq!(result.path_to(&(3, 0)).unwrap().len(), 10);
+        assert_eq!(result.distance(&(2, 1)), Some(15));
+        assert_eq!(result.order.len(), 14);
+        assert_eq!(bfs(&grid(), (0, 0)).distance(&(3, 0)), Some(3));
+
+        let manhattan = |node: &(i64, i64)| ((3 - node.0).abs() + node.1.abs()) as u64;
+        let result = astar(&grid(), (0, 0), &(3, 0), manhattan);
+        assert_eq!(result.distance(&(3, 0)), Some(9));
+        assert_eq!(result.path_to(&(3, 0)), dijkstra(&grid(), (0, 0)).path_to(&(3, 0)));
+        assert_eq!(result.order.last(), Some(&(3, 0)));
+        assert!(astar(&grid(), (0, 0), &(9, 9), manhattan).distance(&(9, 9)).is_none());
+    }
+}
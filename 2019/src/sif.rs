@@ -0,0 +1,311 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use image::{ImageBuffer, Rgb, RgbImage};
+use crate::frames::Palette;
+use crate::ocr::{self, OcrError};
+
+pub const BLACK: i8 = 0;
+pub const WHITE: i8 = 1;
+pub const TRANSPARENT: i8 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SifError {
+    EmptyDimensions,
+    // The data doesn't split into whole layers of width * height pixels.
+    SizeMismatch { length: usize, layer_size: usize },
+    InvalidDigit { position: usize, character: char },
+    // A bitmap row that isn't as wide as the first row.
+    RaggedRow { row: usize, width: usize, expected: usize },
+    // A bitmap pixel that isn't a single digit.
+    InvalidPixel { row: usize, column: usize, value: i8 },
+    InvalidLayerCount,
+}
+
+impl fmt::Display for SifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SifError::EmptyDimensions => write!(f, "Width and height must be at least 1"),
+            SifError::SizeMismatch { length, layer_size } =>
+                write!(f, "{} pixels don't split into layers of {} pixels", length, layer_size),
+            SifError::InvalidDigit { position, character } =>
+                write!(f, "Expected a digit at position {}, found {:?}", position, character),
+            SifError::RaggedRow { row, width, expected } =>
+                write!(f, "Row {} is {} pixels wide, expected {}", row, width, expected),
+            SifError::InvalidPixel { row, column, value } =>
+                write!(f, "Pixel {} at row {}, column {} isn't a digit", value, row, column),
+            SifError::InvalidLayerCount => write!(f, "An image needs at least one layer"),
+        }
+    }
+}
+
+impl std::error::Error for SifError {}
+
+// A Space Image Format image. Layers are listed front to back, so the first layer
+// that isn't transparent at a pixel decides its color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    image_layers: Vec<ImageLayer>,
+}
+
+impl Image {
+    // Surrounding whitespace, like the trailing newline of a puzzle input, is ignored.
+    pub fn parse(pixel_data: &str, width: usize, height: usize) -> Result<Image, SifError> {
+        if width == 0 || height == 0 { return Err(SifError::EmptyDimensions); }
+
+        let all_pixels = pixel_data
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(position, character)| character.to_digit(10)
+                .map(|x| x as i8)
+                .ok_or(SifError::InvalidDigit { position, character }))
+            .collect::<Result<Vec<i8>, SifError>>()?;
+
+        let layer_size = width * height;
+        if all_pixels.is_empty() || all_pixels.len() % layer_size != 0 {
+            return Err(SifError::SizeMismatch { length: all_pixels.len(), layer_size });
+        }
+
+        let image_layers = all_pixels
+            .chunks(layer_size)
+            .map(|pixels| ImageLayer::parse(pixels, width))
+            .collect();
+        Ok(Image { width, height, image_layers })
+    }
+
+    // Spreads a bitmap over `layer_count` layers. Each pixel gets its color on one layer,
+    // taking turns from the front, with transparent pixels in front of it and the opposite
+    // color behind, so the layers only add up to the bitmap when composited.
+    pub fn encode(bitmap: &[Vec<i8>], layer_count: usize) -> Result<Image, SifError> {
+        if layer_count == 0 { return Err(SifError::InvalidLayerCount); }
+        let height = bitmap.len();
+        let width = bitmap.first().map_or(0, |x| x.len());
+        if width == 0 { return Err(SifError::EmptyDimensions); }
+
+        for (row, pixels) in bitmap.iter().enumerate() {
+            if pixels.len() != width {
+                return Err(SifError::RaggedRow { row, width: pixels.len(), expected: width });
+            }
+            if let Some(column) = pixels.iter().position(|x| !(0..=9).contains(x)) {
+                return Err(SifError::InvalidPixel { row, column, value: pixels[column] });
+            }
+        }
+
+        let image_layers = (0..layer_count).map(|layer_index| ImageLayer {
+            width,
+            height,
+            pixels: bitmap.iter().enumerate().map(|(row, pixels)| {
+                pixels.iter().enumerate().map(|(column, color)| {
+                    let color_layer = (row * width + column) % layer_count;
+                    if layer_index < color_layer { TRANSPARENT }
+                    else if layer_index == color_layer { *color }
+                    else { Image::opposite_color(*color) }
+                }).collect()
+            }).collect(),
+        }).collect();
+        Ok(Image { width, height, image_layers })
+    }
+
+    fn opposite_color(color: i8) -> i8 {
+        match color {
+            BLACK => WHITE,
+            WHITE => BLACK,
+            _ => TRANSPARENT
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn layers(&self) -> &[ImageLayer] {
+        &self.image_layers
+    }
+
+    pub fn get_image_layer_with_fewest_given_pixel_value(&self, pixel_value: i8) -> &ImageLayer {
+        self.image_layers
+            .iter()
+            .min_by_key(|x| x.get_number_of_pixels_matching(pixel_value))
+            .unwrap()
+    }
+
+    pub fn get_pixel_color(&self, row: usize, column: usize) -> i8 {
+        self.image_layers
+            .iter()
+            .map(|x| x.pixels[row][column])
+            .find(|x| *x != TRANSPARENT)
+            .unwrap_or(TRANSPARENT)
+    }
+
+    // Colors seen from the front, row by row.
+    pub fn composite(&self) -> Vec<Vec<i8>> {
+        (0..self.height)
+            .map(|row| (0..self.width).map(|column| self.get_pixel_color(row, column)).collect())
+            .collect()
+    }
+
+    pub fn print(&self) -> String {
+        self.composite().iter()
+            .map(|row| row.iter().map(|x| x.to_string()).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // White pixels are the ones drawing the message.
+    pub fn decode_message(&self) -> Result<String, OcrError> {
+        let grid = self.composite().iter()
+            .map(|row| row.iter().map(|x| *x == WHITE).collect())
+            .collect::<Vec<Vec<bool>>>();
+        ocr::recognize(&grid)
+    }
+
+    // Each pixel becomes a `scale` sized square. Colors missing from the palette,
+    // transparent included unless it's given one, get the palette background.
+    pub fn to_rgb_image(&self, palette: &Palette<i8>, scale: u32) -> RgbImage {
+        let scale = scale.max(1);
+        let composite = self.composite();
+        ImageBuffer::from_fn(self.width as u32 * scale, self.height as u32 * scale, |x, y| {
+            Rgb(palette.color(&composite[(y / scale) as usize][(x / scale) as usize]))
+        })
+    }
+
+    pub fn save_png(&self, path: &Path, palette: &Palette<i8>, scale: u32) -> io::Result<()> {
+        self.to_rgb_image(palette, scale).save(path)
+    }
+
+    // The layers written back out as SIF data.
+    pub fn to_sif(&self) -> String {
+        self.image_layers.iter()
+            .flat_map(|layer| layer.pixels.iter().flatten())
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    pub fn default_palette() -> Palette<i8> {
+        Palette::new([0, 0, 0]).with(WHITE, [255, 255, 255])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageLayer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec<i8>>,
+}
+
+impl ImageLayer {
+    fn parse(pixels: &[i8], width: usize) -> ImageLayer {
+        let split_pixels = pixels
+            .chunks(width)
+            .map(|x| x.to_vec())
+            .collect::<Vec<Vec<i8>>>();
+
+        ImageLayer { width, height: split_pixels.len(), pixels: split_pixels }
+    }
+
+    pub fn pixels(&self) -> &[Vec<i8>] {
+        &self.pixels
+    }
+
+    pub fn get_number_of_pixels_matching(&self, num: i8) -> usize {
+        self.pixels.iter().flatten().filter(|x| **x == num).count()
+    }
+
+    pub fn get_number_of_pixels_not_matching(&self, num: i8) -> usize {
+        self.width * self.height - self.get_number_of_pixels_matching(num)
+    }
+
+    // How many pixels hold each digit, indexed by the digit.
+    pub fn digit_counts(&self) -> [usize; 10] {
+        let mut counts = [0; 10];
+        self.pixels.iter().flatten().for_each(|x| counts[*x as usize] += 1);
+        counts
+    }
+}
+
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.image_layers.iter().try_for_each(|x| write!(f, "{}", x))
+    }
+}
+
+impl fmt::Display for ImageLayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sif::{Image, SifError};
+    use crate::frames::Palette;
+    use image::Rgb;
+
+    #[test]
+    fn parses_pixels() {
+        assert_eq!(Image::parse("123456111222", 3, 2).unwrap().to_string(), "[[1, 2, 3], [4, 5, 6]][[1, 1, 1], [2, 2, 2]]");
+        assert_eq!(Image::parse("123456\n", 3, 2).unwrap().layers().len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_data() {
+        assert_eq!(Image::parse("12345", 3, 2), Err(SifError::SizeMismatch { length: 5, layer_size: 6 }));
+        assert_eq!(Image::parse("", 3, 2), Err(SifError::SizeMismatch { length: 0, layer_size: 6 }));
+        assert_eq!(Image::parse("12a456", 3, 2), Err(SifError::InvalidDigit { position: 2, character: 'a' }));
+        assert_eq!(Image::parse("123456", 0, 2), Err(SifError::EmptyDimensions));
+    }
+
+    #[test]
+    fn gets_count_of_matching_pixels() {
+        let image = Image::parse("100006", 3, 2).unwrap();
+        assert_eq!(image.layers()[0].get_number_of_pixels_matching(0), 4);
+        assert_eq!(image.layers()[0].get_number_of_pixels_not_matching(0), 2);
+        assert_eq!(image.layers()[0].digit_counts(), [4, 1, 0, 0, 0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn get_layer_with_fewest_0() {
+        assert_eq!(
+            Image::parse("103456111222", 3, 2).unwrap().get_image_layer_with_fewest_given_pixel_value(0).to_string(),
+            "[[1, 1, 1], [2, 2, 2]]");
+    }
+
+    #[test]
+    fn gets_message() {
+        assert_eq!(Image::parse("0222112222120000", 2, 2).unwrap().print(), "01\n10");
+    }
+
+    #[test]
+    fn encodes_bitmap_into_layers() {
+        let bitmap = vec![vec![0, 1, 1], vec![1, 2, 0]];
+        let image = Image::encode(&bitmap, 4).unwrap();
+
+        assert_eq!(image.layers().len(), 4);
+        assert_eq!(image.composite(), bitmap);
+        assert_eq!(Image::parse(&image.to_sif(), 3, 2).unwrap(), image);
+        assert_eq!(Image::encode(&bitmap, 1).unwrap().to_sif(), "011120");
+
+        assert_eq!(Image::encode(&bitmap, 0), Err(SifError::InvalidLayerCount));
+        assert_eq!(Image::encode(&[vec![0, 1], vec![1]], 2), Err(SifError::RaggedRow { row: 1, width: 1, expected: 2 }));
+        assert_eq!(Image::encode(&[vec![0, 12]], 2), Err(SifError::InvalidPixel { row: 0, column: 1, value: 12 }));
+    }
+
+    #[test]
+    fn renders_with_palette() {
+        let image = Image::parse("0222112222120000", 2, 2).unwrap();
+        let palette = Palette::new([0, 0, 255]).with(1, [255, 255, 255]);
+        let rendered = image.to_rgb_image(&palette, 3);
+
+        assert_eq!(rendered.dimensions(), (6, 6));
+        assert_eq!(*rendered.get_pixel(0, 0), Rgb([0, 0, 255]));
+        assert_eq!(*rendered.get_pixel(5, 2), Rgb([255, 255, 255]));
+        assert_eq!(*rendered.get_pixel(0, 3), Rgb([255, 255, 255]));
+    }
+}
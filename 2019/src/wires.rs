@@ -0,0 +1,474 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Line {
+    pub point1: Point,
+    pub point2: Point,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn manhattan_distance(&self, other: &Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+impl Line {
+    pub fn length(&self) -> i64 {
+        self.point1.manhattan_distance(&self.point2)
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.point1.x == self.point2.x && self.point1.y != self.point2.y
+    }
+
+    fn min_x(&self) -> i64 { self.point1.x.min(self.point2.x) }
+    fn max_x(&self) -> i64 { self.point1.x.max(self.point2.x) }
+    fn min_y(&self) -> i64 { self.point1.y.min(self.point2.y) }
+    fn max_y(&self) -> i64 { self.point1.y.max(self.point2.y) }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.point1, self.point2)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireError {
+    InvalidDirection(String),
+    InvalidLength(String),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WireError::InvalidDirection(instruction) =>
+                write!(f, "Invalid direction in {:?}, expected one of U, D, L or R", instruction),
+            WireError::InvalidLength(instruction) =>
+                write!(f, "Invalid length in {:?}", instruction),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+// A wire starting at the origin, with y growing upwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wire {
+    pub lines: Vec<Line>,
+    // Steps taken along the wire before each line starts.
+    steps_before: Vec<i64>,
+}
+
+impl Wire {
+    pub fn parse(path: &str) -> Result<Wire, WireError> {
+        let mut previous_point = Point::ORIGIN;
+        let lines = path.trim().split(',').map(|instruction| {
+            let instruction = instruction.trim();
+            let units = instruction.get(1..)
+                .and_then(|x| x.parse::<i64>().ok())
+                .filter(|x| *x >= 0)
+                .ok_or_else(|| WireError::InvalidLength(instruction.to_string()))?;
+            let next_point = match instruction.chars().next() {
+                Some('R') => Point { x: previous_point.x + units, y: previous_point.y },
+                Some('L') => Point { x: previous_point.x - units, y: previous_point.y },
+                Some('U') => Point { x: previous_point.x, y: previous_point.y + units },
+                Some('D') => Point { x: previous_point.x, y: previous_point.y - units },
+                _ => return Err(WireError::InvalidDirection(instruction.to_string()))
+            };
+            let line = Line { point1: previous_point, point2: next_point };
+            previous_point = next_point;
+            Ok(line)
+        }).collect::<Result<Vec<Line>, WireError>>()?;
+
+        Ok(Wire::from_lines(lines))
+    }
+
+    pub fn from_lines(lines: Vec<Line>) -> Wire {
+        let steps_before = lines.iter()
+            .scan(0, |steps, line| {
+                let before = *steps;
+                *steps += line.length();
+                Some(before)
+            })
+            .collect();
+        Wire { lines, steps_before }
+    }
+
+    // Steps along the wire to reach `point` on its line at `index`.
+    pub fn steps_to(&self, index: usize, point: &Point) -> i64 {
+        self.steps_before[index] + self.lines[index].point1.manhattan_distance(point)
+    }
+}
+
+// A point where two or more wires meet, with the fewest steps each of them takes to get there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossing {
+    pub point: Point,
+    pub steps: BTreeMap<usize, i64>,
+}
+
+impl Crossing {
+    pub fn distance_from_origin(&self) -> i64 {
+        self.point.manhattan_distance(&Point::ORIGIN)
+    }
+
+    pub fn combined_steps(&self) -> i64 {
+        self.steps.values().sum()
+    }
+}
+
+pub struct CrossedWires {
+    wires: Vec<Wire>,
+}
+
+// A line of one of the wires, as the sweep sees it.
+#[derive(Copy, Clone)]
+struct Segment {
+    wire: usize,
+    index: usize,
+    line: Line,
+}
+
+impl CrossedWires {
+    pub fn new(wires: Vec<Wire>) -> CrossedWires {
+        CrossedWires { wires }
+    }
+
+    // One wire path per line, blank lines are skipped.
+    pub fn parse(input: &str) -> Result<CrossedWires, WireError> {
+        let wires = input.lines()
+            .filter(|x| !x.trim().is_empty())
+            .map(Wire::parse)
+            .collect::<Result<Vec<Wire>, WireError>>()?;
+        Ok(CrossedWires { wires })
+    }
+
+    pub fn wires(&self) -> &[Wire] {
+        &self.wires
+    }
+
+    // Draws each wire in its own color over the origin and every crossing, labelling the
+    // crossing closest to the origin and the one with the fewest combined steps. The view box
+    // fits the wires, with y flipped so up is up.
+    pub fn to_svg(&self) -> String {
+        const COLORS: [&str; 6] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2"];
+
+        let points = || self.wires.iter()
+            .flat_map(|wire| wire.lines.iter().map(|x| x.point2))
+            .chain(std::iter::once(Point::ORIGIN));
+        let min_x = points().map(|x| x.x).min().unwrap();
+        let max_x = points().map(|x| x.x).max().unwrap();
+        let min_y = points().map(|x| x.y).min().unwrap();
+        let max_y = points().map(|x| x.y).max().unwrap();
+        let size = (max_x - min_x).max(max_y - min_y).max(1) as f64;
+        let margin = size * 0.05;
+        let stroke = size / 400.0;
+        let radius = stroke * 3.0;
+        let font_size = size / 40.0;
+
+        let mut svg = String::new();
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+                 min_x as f64 - margin, -max_y as f64 - margin,
+                 (max_x - min_x) as f64 + 2.0 * margin, (max_y - min_y) as f64 + 2.0 * margin).unwrap();
+        writeln!(svg, r#"<rect x="{}" y="{}" width="100%" height="100%" fill="white"/>"#,
+                 min_x as f64 - margin, -max_y as f64 - margin).unwrap();
+
+        for (index, wire) in self.wires.iter().enumerate() {
+            let path = std::iter::once(Point::ORIGIN)
+                .chain(wire.lines.iter().map(|x| x.point2))
+                .map(|x| format!("{},{}", x.x, -x.y))
+                .collect::<Vec<String>>()
+                .join(" ");
+            writeln!(svg, r#"<polyline class="wire" points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                     path, COLORS[index % COLORS.len()], stroke).unwrap();
+        }
+
+        writeln!(svg, r#"<circle class="origin" cx="0" cy="0" r="{}" fill="black"/>"#, radius * 1.5).unwrap();
+        let crossings = self.crossings();
+        for crossing in &crossings {
+            writeln!(svg, r#"<circle class="crossing" cx="{}" cy="{}" r="{}" fill="red"/>"#,
+                     crossing.point.x, -crossing.point.y, radius).unwrap();
+        }
+
+        let closest = crossings.iter().min_by_key(|x| (x.distance_from_origin(), x.point));
+        let fewest_steps = crossings.iter().min_by_key(|x| (x.combined_steps(), x.point));
+        let highlights = [
+            (closest, format!("closest: {}", closest.map_or(0, |x| x.distance_from_origin())), "#d62728"),
+            (fewest_steps, format!("fewest steps: {}", fewest_steps.map_or(0, |x| x.combined_steps())), "#17becf"),
+        ];
+        for (offset, (crossing, label, color)) in highlights.iter().enumerate() {
+            if let Some(crossing) = crossing {
+                let (x, y) = (crossing.point.x, -crossing.point.y);
+                writeln!(svg, r#"<circle class="highlight" cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                         x, y, radius * 3.0, color, stroke).unwrap();
+                writeln!(svg, r#"<text x="{}" y="{}" font-size="{}" fill="{}">{} at {}</text>"#,
+                         x as f64 + radius * 4.0, y as f64 + font_size * offset as f64, font_size,
+                         color, label, crossing.point).unwrap();
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn save_svg(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+
+    // Every point other than the origin where lines of different wires meet, ordered by point.
+    // Horizontal and vertical lines are matched with a sweep over x, collinear lines by sorting
+    // them along their shared row or column. Lines are only ever paired with lines of other
+    // wires, so this runs in O((w n + k) log n) for w wires, n lines and k meetings.
+    pub fn crossings(&self) -> Vec<Crossing> {
+        let segments = self.wires.iter()
+            .enumerate()
+            .flat_map(|(wire, x)| x.lines.iter()
+                .enumerate()
+                .map(move |(index, line)| Segment { wire, index, line: *line }))
+            .collect::<Vec<Segment>>();
+
+        let mut steps: HashMap<Point, BTreeMap<usize, i64>> = HashMap::new();
+        let mut meet = |point: Point, segments: [&Segment; 2]| {
+            if point == Point::ORIGIN { return; }
+            let wires = steps.entry(point).or_default();
+            for segment in segments.iter() {
+                let steps = self.wires[segment.wire].steps_to(segment.index, &point);
+                let fewest = wires.entry(segment.wire).or_insert(steps);
+                *fewest = steps.min(*fewest);
+            }
+        };
+
+        CrossedWires::sweep_perpendicular(&segments, &mut meet);
+        CrossedWires::overlap_collinear(&segments, &mut meet);
+
+        let mut crossings = steps.into_iter()
+            .map(|(point, steps)| Crossing { point, steps })
+            .collect::<Vec<Crossing>>();
+        crossings.sort_by_key(|x| x.point);
+        crossings
+    }
+
+    pub fn closest_crossing(&self) -> Option<Crossing> {
+        self.crossings().into_iter().min_by_key(|x| (x.distance_from_origin(), x.point))
+    }
+
+    pub fn fewest_steps_crossing(&self) -> Option<Crossing> {
+        self.crossings().into_iter().min_by_key(|x| (x.combined_steps(), x.point))
+    }
+
+    // Horizontal lines are active between their ends while x moves right, and each vertical
+    // line asks for the active ones within its y range. Each wire has its own active lines so
+    // a vertical line only asks the other wires. At the same x lines are added before the
+    // vertical ones ask and removed after, so touching ends count.
+    fn sweep_perpendicular<F: FnMut(Point, [&Segment; 2])>(segments: &[Segment], meet: &mut F) {
+        const ADD: u8 = 0;
+        const ASK: u8 = 1;
+        const REMOVE: u8 = 2;
+
+        let mut events = segments.iter()
+            .enumerate()
+            .flat_map(|(id, segment)| if segment.line.is_vertical() {
+                vec![(segment.line.min_x(), ASK, id)]
+            } else {
+                vec![(segment.line.min_x(), ADD, id), (segment.line.max_x(), REMOVE, id)]
+            })
+            .collect::<Vec<(i64, u8, usize)>>();
+        events.sort();
+
+        let wires = segments.iter().map(|x| x.wire + 1).max().unwrap_or(0);
+        let mut active: Vec<BTreeSet<(i64, usize)>> = vec![BTreeSet::new(); wires];
+        for (x, kind, id) in events {
+            let line = segments[id].line;
+            let wire = segments[id].wire;
+            match kind {
+                ADD => { active[wire].insert((line.point1.y, id)); }
+                REMOVE => { active[wire].remove(&(line.point1.y, id)); }
+                _ => {
+                    for other in active.iter().enumerate().filter(|(x, _)| *x != wire).map(|(_, x)| x) {
+                        for (y, horizontal) in other.range((line.min_y(), 0)..=(line.max_y(), usize::MAX)) {
+                            meet(Point { x, y: *y }, [&segments[*horizontal], &segments[id]]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Lines on the same row, or the same column, meet at every point they share.
+    fn overlap_collinear<F: FnMut(Point, [&Segment; 2])>(segments: &[Segment], meet: &mut F) {
+        let mut rows: HashMap<i64, Vec<&Segment>> = HashMap::new();
+        let mut columns: HashMap<i64, Vec<&Segment>> = HashMap::new();
+        for segment in segments {
+            if segment.line.is_vertical() {
+                columns.entry(segment.line.point1.x).or_default().push(segment);
+            } else {
+                rows.entry(segment.line.point1.y).or_default().push(segment);
+            }
+        }
+
+        let mut overlap = |lines: Vec<&Segment>, start: fn(&Line) -> i64, end: fn(&Line) -> i64,
+                           point: &dyn Fn(i64, &Line) -> Point| {
+            // Each pair is found once, from the line that comes first in this order.
+            let key = |x: &Segment| (start(&x.line), x.wire, x.index);
+            let mut by_wire: BTreeMap<usize, Vec<&Segment>> = BTreeMap::new();
+            for line in &lines {
+                by_wire.entry(line.wire).or_default().push(line);
+            }
+            by_wire.values_mut().for_each(|x| x.sort_by_key(|x| key(x)));
+
+            for first in &lines {
+                for (_, other) in by_wire.iter().filter(|(wire, _)| **wire != first.wire) {
+                    // Lines further on start later, so once one starts past this end none overlap.
+                    let after = other.partition_point(|x| key(x) <= key(first));
+                    for second in other[after..].iter().take_while(|x| start(&x.line) <= end(&first.line)) {
+                        for along in start(&second.line)..=end(&first.line).min(end(&second.line)) {
+                            meet(point(along, &first.line), [first, second]);
+                        }
+                    }
+                }
+            }
+        };
+
+        for (_, row) in rows {
+            overlap(row, Line::min_x, Line::max_x, &|x, line| Point { x, y: line.point1.y });
+        }
+        for (_, column) in columns {
+            overlap(column, Line::min_y, Line::max_y, &|y, line| Point { x: line.point1.x, y });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wires::{CrossedWires, Point, Wire, WireError};
+    use std::collections::{BTreeMap, HashMap};
+
+    fn crossed(paths: &[&str]) -> CrossedWires {
+        CrossedWires::parse(&paths.join("\n")).unwrap()
+    }
+
+    // Walks every wire point by point, which is slow but obviously right.
+    fn brute_force(wires: &CrossedWires) -> Vec<(Point, BTreeMap<usize, i64>)> {
+        let mut visits: HashMap<Point, BTreeMap<usize, i64>> = HashMap::new();
+        for (wire_index, wire) in wires.wires().iter().enumerate() {
+            let mut steps = 0;
+            for line in &wire.lines {
+                let (dx, dy) = ((line.point2.x - line.point1.x).signum(), (line.point2.y - line.point1.y).signum());
+                for step in 1..=line.length() {
+                    let point = Point { x: line.point1.x + dx * step, y: line.point1.y + dy * step };
+                    visits.entry(point).or_default().entry(wire_index).or_insert(steps + step);
+                }
+                steps += line.length();
+            }
+        }
+        let mut crossings = visits.into_iter()
+            .filter(|(point, wires)| wires.len() > 1 && *point != Point::ORIGIN)
+            .collect::<Vec<_>>();
+        crossings.sort_by_key(|x| x.0);
+        crossings
+    }
+
+    #[test]
+    fn parses_wires() {
+        let wire = Wire::parse("R75,D30,L83,U83").unwrap();
+        assert_eq!(wire.lines.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(".."),
+                   "(0,0),(75,0)..(75,0),(75,-30)..(75,-30),(-8,-30)..(-8,-30),(-8,53)");
+        assert_eq!(wire.steps_to(2, &Point { x: 0, y: -30 }), 180);
+
+        assert_eq!(Wire::parse("R7,X3"), Err(WireError::InvalidDirection(String::from("X3"))));
+        assert_eq!(Wire::parse("R7,U"), Err(WireError::InvalidLength(String::from("U"))));
+        assert_eq!(Wire::parse("R7,U-2"), Err(WireError::InvalidLength(String::from("U-2"))));
+    }
+
+    #[test]
+    fn finds_crossings_with_steps() {
+        let wires = crossed(&["R8,U5,L5,D3", "U7,R6,D4,L4"]);
+        let crossings = wires.crossings();
+
+        assert_eq!(crossings.iter().map(|x| x.point).collect::<Vec<Point>>(), vec![Point { x: 3, y: 3 }, Point { x: 6, y: 5 }]);
+        assert_eq!(crossings[0].steps, vec![(0, 20), (1, 20)].into_iter().collect());
+        assert_eq!(wires.closest_crossing().unwrap().distance_from_origin(), 6);
+        assert_eq!(wires.fewest_steps_crossing().unwrap().combined_steps(), 30);
+    }
+
+    #[test]
+    fn solves_examples() {
+        let wires = crossed(&["R75,D30,R83,U83,L12,D49,R71,U7,L72", "U62,R66,U55,R34,D71,R55,D58,R83"]);
+        assert_eq!(wires.closest_crossing().unwrap().distance_from_origin(), 159);
+        assert_eq!(wires.fewest_steps_crossing().unwrap().combined_steps(), 610);
+
+        let wires = crossed(&["R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51", "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"]);
+        assert_eq!(wires.closest_crossing().unwrap().distance_from_origin(), 135);
+        assert_eq!(wires.fewest_steps_crossing().unwrap().combined_steps(), 410);
+    }
+
+    #[test]
+    fn finds_collinear_overlaps_and_ignores_the_origin() {
+        let wires = crossed(&["R5,U2", "U1,R2,D1,R4", "L3,R3"]);
+        let crossings = wires.crossings();
+        assert_eq!(crossings.iter().map(|x| (x.point.x, x.point.y)).collect::<Vec<_>>(), vec![(2, 0), (3, 0), (4, 0), (5, 0)]);
+        assert_eq!(crossings[3].steps, vec![(0, 5), (1, 7)].into_iter().collect());
+    }
+
+    #[test]
+    fn ignores_wires_retracing_themselves() {
+        let back_and_forth = vec!["R10,U3,D3,L10"; 200].join(",");
+        let wires = crossed(&[&back_and_forth, "U1,R5,D2,R2,U4,R20"]);
+        let crossings = wires.crossings();
+        assert_eq!(crossings.iter().map(|x| (x.point.x, x.point.y)).collect::<Vec<_>>(), vec![(5, 0), (7, 0), (10, 3)]);
+        assert_eq!(crossings.into_iter().map(|x| (x.point, x.steps)).collect::<Vec<_>>(), brute_force(&wires));
+    }
+
+    #[test]
+    fn draws_wires_and_crossings_as_svg() {
+        let svg = crossed(&["R8,U5,L5,D3", "U7,R6,D4,L4"]).to_svg();
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-0.4 -7.4 8.8 7.8">"#));
+        assert!(svg.contains(r##"points="0,0 8,0 8,-5 3,-5 3,-2" fill="none" stroke="#1f77b4""##));
+        assert!(svg.contains(r##"points="0,0 0,-7 6,-7 6,-3 2,-3" fill="none" stroke="#ff7f0e""##));
+        assert_eq!(svg.matches(r#"class="crossing""#).count(), 2);
+        assert!(svg.contains("closest: 6 at (3,3)</text>"));
+        assert!(svg.contains("fewest steps: 30 at (6,5)</text>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn handles_any_number_of_wires_like_a_brute_force_walk() {
+        let mut seed: u64 = 7;
+        let mut random = |limit: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % limit
+        };
+        for _ in 0..20 {
+            let paths = (0..4).map(|_| (0..30)
+                .map(|_| format!("{}{}", ["U", "D", "L", "R"][random(4) as usize], random(12)))
+                .collect::<Vec<String>>()
+                .join(","))
+                .collect::<Vec<String>>();
+            let wires = CrossedWires::parse(&paths.join("\n")).unwrap();
+
+            let expected = brute_force(&wires);
+            let actual = wires.crossings().into_iter().map(|x| (x.point, x.steps)).collect::<Vec<_>>();
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,516 @@
+//! Differential tests running the interpreters the days used to carry around
+//! next to the code days 2, 5 and 7 now share through the library. Any drift in
+//! behaviour between the two shows up here before it shows up as a wrong answer.
+
+use advent_of_code::amplifiers::AmplifierCircuit;
+use advent_of_code::{diagnostics, gravity_assist};
+use permutator::Permutation;
+
+static TWO: &str = include_str!("../data/two.data");
+static FIVE: &str = include_str!("../data/five.data");
+static SEVEN: &str = include_str!("../data/seven.data");
+
+#[allow(unused, clippy::all)]
+mod legacy_day02 {
+    pub fn intcode(input: &str, restore_gravity: bool) -> Vec<String> {
+        let mut memory: Vec<String> = input.split(',').map(|s| s.to_string()).collect();
+        if restore_gravity {
+            memory[1] = String::from("12");
+            memory[2] = String::from("2");
+        }
+        let mut current_opcode_position = 0;
+        while (current_opcode_position < memory.len() - 1)
+            && memory[current_opcode_position] != String::from("99") {
+            let current_opcode = &memory[current_opcode_position];
+            let input_position_1 = memory[current_opcode_position + 1].parse::<usize>().unwrap();
+            let input_position_2 = memory[current_opcode_position + 2].parse::<usize>().unwrap();
+            let output_position = memory[current_opcode_position + 3].parse::<usize>().unwrap();
+
+            let input1 = memory[input_position_1].parse::<i32>().unwrap();
+            let input2 = memory[input_position_2].parse::<i32>().unwrap();
+            match current_opcode.as_str() {
+                "1" => {
+                    let x = (input1 + input2).to_string();
+                    memory[output_position] = x
+                }
+                "2" => {
+                    let x1 = (input1 * input2).to_string();
+                    memory[output_position] = x1
+                }
+                _ => {}
+            }
+
+            current_opcode_position += 4;
+        }
+        memory
+    }
+
+    pub fn intcode_with_noun_and_verb<'a>(codes: &'a mut Vec<String>, noun: &i32, verb: &i32) -> &'a Vec<String> {
+        codes[1] = noun.to_string();
+        codes[2] = verb.to_string();
+        let mut current_opcode_position = 0;
+
+        while (current_opcode_position < codes.len() - 1)
+            && codes[current_opcode_position] != String::from("99") {
+
+            let current_opcode = &codes[current_opcode_position];
+            let input_position_1 = codes[current_opcode_position + 1].parse::<usize>().unwrap();
+            let input_position_2 = codes[current_opcode_position + 2].parse::<usize>().unwrap();
+            let output_position = codes[current_opcode_position + 3].parse::<usize>().unwrap();
+
+            let input1 = codes[input_position_1].parse::<i32>().unwrap();
+            let input2 = codes[input_position_2].parse::<i32>().unwrap();
+            match current_opcode.as_str() {
+                "1" => {
+                    let x = (input1 + input2).to_string();
+                    codes[output_position] = x
+                }
+                "2" => {
+                    let x1 = (input1 * input2).to_string();
+                    codes[output_position] = x1
+                }
+                _ => {}
+            }
+
+            current_opcode_position += 4;
+        }
+        codes
+    }
+}
+
+#[allow(unused, clippy::all)]
+mod legacy_day05 {
+    pub fn intcode(input: &str, device_id: &str) -> (Vec<String>, i32) {
+        let mut codes: Vec<String> = input.split(',').map(|s| s.to_string()).collect();
+        let mut current_opcode_position = 0;
+
+        while (current_opcode_position < codes.len() - 1)
+            && codes[current_opcode_position] != String::from("99") {
+
+            let current_instruction = &codes[current_opcode_position].parse::<usize>().unwrap();
+            let current_opcode = current_instruction % 10;
+            let input1 = get_input1(&codes, &current_opcode_position, current_instruction);
+
+            match current_opcode {
+                1 => add(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                2 => multiply(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                3 => get_input(device_id, &mut codes, &mut current_opcode_position),
+                4 => {
+                    current_opcode_position += 2;
+                    let next_command = &codes[current_opcode_position];
+                    if input1 != 0 && *next_command != String::from("99") {
+                        panic!("It can't be non-zero")
+                    }
+                    if *next_command == String::from("99") {
+                        return (codes, input1);
+                    }
+                }
+                5 => jump_if_true(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                6 => jump_if_false(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                7 => on_first_parameter_lesser_than_second(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                8 => on_both_parameters_equal(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                _ => {
+                    panic!("Invalid opcode")
+                }
+            }
+        }
+        (codes, 0)
+    }
+
+    fn on_both_parameters_equal(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_input2(&mut codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = if input1 == input2 { 1 } else { 0 }.to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn on_first_parameter_lesser_than_second(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_input2(&mut codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = if input1 < input2 { 1 } else { 0 }.to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn jump_if_false(mut codes: &mut Vec<String>, mut current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_input2(&mut codes, &mut current_opcode_position, current_instruction);
+
+        if input1 == 0 {
+            *current_opcode_position = input2 as usize
+        } else {
+            *current_opcode_position += 3;
+        }
+    }
+
+    fn jump_if_true(mut codes: &mut Vec<String>, mut current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_input2(&mut codes, &mut current_opcode_position, current_instruction);
+        if input1 != 0 {
+            *current_opcode_position = input2 as usize
+        } else {
+            *current_opcode_position += 3;
+        }
+    }
+
+    fn get_input(device_id: &str, mut codes: &mut Vec<String>, current_opcode_position: &mut usize) -> () {
+        let output_position = get_number(&mut codes, &current_opcode_position, 1);
+        codes[output_position] = String::from(device_id);
+        *current_opcode_position += 2;
+    }
+
+    fn multiply(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_input2(&mut codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = (input1 * input2).to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn add(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_input2(&codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = (input1 + input2).to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn get_number(codes: &mut Vec<String>, current_opcode_position: &usize, relative_index_from_opcode: usize) -> usize {
+        codes[current_opcode_position + relative_index_from_opcode].parse::<usize>().unwrap()
+    }
+
+    fn get_input1(codes: &Vec<String>, current_opcode_position: &usize, current_instruction: &usize) -> i32 {
+        let input_mode = (current_instruction / 100) % 10;
+        let input_parameter = codes[current_opcode_position + 1].parse::<i32>().unwrap();
+        get_input_by_mode(codes, input_mode, input_parameter as usize)
+    }
+
+    fn get_input2(codes: & Vec<String>, current_opcode_position: &usize, current_instruction: &usize) -> i32 {
+        let input_mode = (current_instruction / 1000) % 10;
+        let input_parameter = codes[current_opcode_position + 2].parse::<i32>().unwrap();
+        get_input_by_mode(codes, input_mode, input_parameter as usize)
+    }
+
+    fn get_input_by_mode(codes: &Vec<String>, input_mode: usize, input_parameter: usize) -> i32 {
+        match input_mode {
+            0 => {
+                codes[input_parameter as usize].parse::<i32>().unwrap()
+            },
+            1 => {
+                input_parameter as i32
+            },
+            _ => {
+                panic!("Invalid position mode {}", input_mode)
+            }
+        }
+    }
+}
+
+#[allow(unused, clippy::all)]
+mod legacy_day07 {
+    pub fn intcode(
+        mut codes: Vec<String>,
+        phase_setting: &str,
+        use_phase_setting: bool,
+        feedback_input: &str,
+        immediate_output_mode: bool,
+        starting_index: usize,
+    ) -> (Vec<String>, i32, usize, bool) {
+        let mut current_opcode_position = starting_index;
+        let mut phase_setting_fed = !use_phase_setting;
+
+        while (current_opcode_position < codes.len() - 1)
+            && codes[current_opcode_position] != String::from("99") {
+            let current_instruction = &codes[current_opcode_position].parse::<usize>().unwrap();
+            let current_opcode = current_instruction % 10;
+            let input1 = get_parameter_1(&codes, &current_opcode_position, current_instruction);
+
+            match current_opcode {
+                1 => add(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                2 => multiply(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                3 => {
+                    let input = get_input(phase_setting, feedback_input, &mut phase_setting_fed);
+                    store_input(
+                        &mut codes,
+                        &mut current_opcode_position,
+                        input,
+                    )
+                }
+                4 => {
+                    current_opcode_position += 2;
+                    let next_command = &codes[current_opcode_position];
+                    if *next_command == String::from("99") {
+                        return (codes, input1, current_opcode_position, false);
+                    }
+                    return (codes, input1, current_opcode_position, false);
+                }
+                5 => jump_if_true(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                6 => jump_if_false(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                7 => on_first_parameter_lesser_than_second(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                8 => on_both_parameters_equal(&mut codes, &mut current_opcode_position, current_instruction, input1),
+                _ => {
+                    panic!("Invalid opcode")
+                }
+            }
+        }
+        return (codes, 0, current_opcode_position, true);
+    }
+
+    fn get_input<'a>(
+        phase_setting: &'a str,
+        feedback_input: &'a str,
+        mut phase_setting_fed: &mut bool,
+    ) -> &'a str {
+        if *phase_setting_fed {
+            feedback_input
+        } else {
+            *phase_setting_fed = true;
+            phase_setting
+        }
+    }
+
+    fn on_both_parameters_equal(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_parameter_2(&mut codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = if input1 == input2 { 1 } else { 0 }.to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn on_first_parameter_lesser_than_second(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_parameter_2(&mut codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = if input1 < input2 { 1 } else { 0 }.to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn jump_if_false(mut codes: &mut Vec<String>, mut current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_parameter_2(&mut codes, &mut current_opcode_position, current_instruction);
+
+        if input1 == 0 {
+            *current_opcode_position = input2 as usize
+        } else {
+            *current_opcode_position += 3;
+        }
+    }
+
+    fn jump_if_true(mut codes: &mut Vec<String>, mut current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_parameter_2(&mut codes, &mut current_opcode_position, current_instruction);
+        if input1 != 0 {
+            *current_opcode_position = input2 as usize
+        } else {
+            *current_opcode_position += 3;
+        }
+    }
+
+    fn store_input(
+        mut codes: &mut Vec<String>,
+        current_opcode_position: &mut usize,
+        input: &str,
+    ) -> () {
+        let output_position = get_number(&mut codes, &current_opcode_position, 1);
+        codes[output_position] = String::from(input);
+        *current_opcode_position += 2;
+    }
+
+    fn multiply(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_parameter_2(&mut codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = (input1 * input2).to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn add(mut codes: &mut Vec<String>, current_opcode_position: &mut usize, current_instruction: &usize, input1: i32) -> () {
+        let input2 = get_parameter_2(&codes, &current_opcode_position, current_instruction);
+        let output_position = get_number(&mut codes, &current_opcode_position, 3);
+
+        codes[output_position] = (input1 + input2).to_string();
+        *current_opcode_position += 4;
+    }
+
+    fn get_number(codes: &mut Vec<String>, current_opcode_position: &usize, relative_index_from_opcode: usize) -> usize {
+        codes[current_opcode_position + relative_index_from_opcode].parse::<usize>().unwrap()
+    }
+
+    fn get_parameter_1(codes: &Vec<String>, current_opcode_position: &usize, current_instruction: &usize) -> i32 {
+        let input_mode = (current_instruction / 100) % 10;
+        let input_parameter = codes[current_opcode_position + 1].parse::<i32>().unwrap();
+        get_input_by_mode(codes, input_mode, input_parameter as usize)
+    }
+
+    fn get_parameter_2(codes: &Vec<String>, current_opcode_position: &usize, current_instruction: &usize) -> i32 {
+        let input_mode = (current_instruction / 1000) % 10;
+        let input_parameter = codes[current_opcode_position + 2].parse::<i32>().unwrap();
+        get_input_by_mode(codes, input_mode, input_parameter as usize)
+    }
+
+    fn get_input_by_mode(codes: &Vec<String>, input_mode: usize, input_parameter: usize) -> i32 {
+        match input_mode {
+            0 => {
+                codes[input_parameter as usize].parse::<i32>().unwrap()
+            }
+            1 => {
+                input_parameter as i32
+            }
+            _ => {
+                panic!("Invalid position mode {}", input_mode)
+            }
+        }
+    }
+}
+
+fn to_memory(input: &str) -> Vec<String> {
+    input.split(',').map(|s| s.to_string()).collect()
+}
+
+fn phase_setting_series(phase_settings: Vec<i64>) -> Vec<Vec<i64>> {
+    let mut permutation = phase_settings.clone().permutation().collect::<Vec<Vec<i64>>>();
+    permutation.push(phase_settings);
+    permutation
+}
+
+fn legacy_serial_signal(program: &str, phase_setting_series: &[i64]) -> i64 {
+    let codes = to_memory(program);
+    phase_setting_series.iter().fold(0, |feedback_input, phase_setting| {
+        let (_, thruster_signal, _, _) = legacy_day07::intcode(
+            codes.clone(),
+            &phase_setting.to_string(),
+            true,
+            &feedback_input.to_string(),
+            true,
+            0,
+        );
+        thruster_signal as i64
+    })
+}
+
+fn legacy_feedback_signal(program: &str, phase_setting_series: &[i64]) -> i64 {
+    let mut amplifier_state: Vec<Option<(usize, Vec<String>)>> = vec![None; phase_setting_series.len()];
+    let mut feedback_input = 0;
+    let mut amplifier_reference = 0;
+    loop {
+        let (starting_index, codes) = amplifier_state[amplifier_reference].clone()
+            .unwrap_or((0, to_memory(program)));
+        let (halt_code, thruster_signal, halt_location, is_complete) = legacy_day07::intcode(
+            codes,
+            &phase_setting_series[amplifier_reference].to_string(),
+            amplifier_state[amplifier_reference].is_none(),
+            &feedback_input.to_string(),
+            true,
+            starting_index,
+        );
+        amplifier_state[amplifier_reference] = Some((halt_location, halt_code));
+
+        if is_complete { return feedback_input as i64; }
+        feedback_input = thruster_signal;
+        amplifier_reference = (amplifier_reference + 1) % phase_setting_series.len();
+    }
+}
+
+#[test]
+fn day02_unit_test_programs_leave_the_same_memory() {
+    let programs = ["2,4,3,4,33", "1,0,0,0,99", "2,3,0,3,99", "2,4,4,5,99,0", "1,1,1,4,99,5,6,0,99"];
+    for program in programs.iter() {
+        assert_eq!(gravity_assist::run(program, None).memory_string(),
+                   legacy_day02::intcode(program, false).join(","),
+                   "program {}", program);
+    }
+}
+
+#[test]
+fn day02_data_leaves_the_same_memory_after_restoring_gravity() {
+    assert_eq!(gravity_assist::run(TWO, Some((12, 2))).memory_string(),
+               legacy_day02::intcode(TWO, true).join(","));
+}
+
+#[test]
+fn day02_data_gives_the_same_output_for_every_noun_and_verb() {
+    let memory = to_memory(TWO);
+    for noun in 0..100 {
+        for verb in 0..100 {
+            let mut legacy_memory = memory.clone();
+            let legacy_output = legacy_day02::intcode_with_noun_and_verb(&mut legacy_memory, &noun, &verb)[0]
+                .parse::<i64>().unwrap();
+            let shared = gravity_assist::run(TWO, Some((noun as i64, verb as i64)));
+            assert_eq!(shared.program[0], legacy_output, "noun {} verb {}", noun, verb);
+        }
+    }
+}
+
+#[test]
+fn day05_unit_test_programs_give_the_same_output() {
+    let programs = [
+        "3,9,8,9,10,9,4,9,99,-1,8",
+        "3,3,1108,-1,8,3,4,3,99",
+        "3,9,7,9,10,9,4,9,99,-1,8",
+        "3,3,1107,-1,8,3,4,3,99",
+        "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+        "3,3,1105,-1,9,1101,0,0,12,4,12,99,1",
+    ];
+    for program in programs.iter() {
+        for device_id in -2..12 {
+            let (legacy_memory, legacy_output) = legacy_day05::intcode(program, &device_id.to_string());
+            let mut shared = diagnostics::run(program, device_id);
+            let (shared_memory, shared_output) = (shared.memory_string(), shared.take_output());
+            assert_eq!(shared_memory, legacy_memory.join(","), "program {} input {}", program, device_id);
+            assert_eq!(shared_output, vec![legacy_output as i64], "program {} input {}", program, device_id);
+        }
+    }
+}
+
+#[test]
+fn day05_unit_test_program_leaves_the_same_memory() {
+    let (legacy_memory, _) = legacy_day05::intcode("1002,4,3,4,33", "1");
+    let shared_memory = diagnostics::run("1002,4,3,4,33", 1).memory_string();
+    assert_eq!(shared_memory, legacy_memory.join(","));
+}
+
+#[test]
+fn day05_data_gives_the_same_diagnostic_code() {
+    for device_id in [1, 5].iter() {
+        let (legacy_memory, legacy_output) = legacy_day05::intcode(FIVE, &device_id.to_string());
+        let (shared, diagnostic_code) = diagnostics::diagnose(FIVE, *device_id);
+
+        assert_eq!(shared.memory_string(), legacy_memory.join(","), "system {}", device_id);
+        assert_eq!(diagnostic_code, legacy_output as i64, "system {}", device_id);
+    }
+}
+
+#[test]
+fn day07_unit_test_programs_give_the_same_signals() {
+    let serial_programs = [
+        "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+        "3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0",
+        "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0",
+    ];
+    for program in serial_programs.iter() {
+        for series in phase_setting_series(vec![0, 1, 2, 3, 4]) {
+            assert_eq!(AmplifierCircuit::serial(program).run(&series), legacy_serial_signal(program, &series),
+                       "program {} series {:?}", program, series);
+        }
+    }
+
+    let feedback_programs = [
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        "3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10",
+    ];
+    for program in feedback_programs.iter() {
+        for series in phase_setting_series(vec![5, 6, 7, 8, 9]) {
+            assert_eq!(AmplifierCircuit::feedback(program).run(&series), legacy_feedback_signal(program, &series),
+                       "program {} series {:?}", program, series);
+        }
+    }
+}
+
+#[test]
+fn day07_data_gives_the_same_signals_for_every_phase_setting() {
+    for series in phase_setting_series(vec![0, 1, 2, 3, 4]) {
+        assert_eq!(AmplifierCircuit::serial(SEVEN).run(&series), legacy_serial_signal(SEVEN, &series),
+                   "series {:?}", series);
+    }
+    for series in phase_setting_series(vec![5, 6, 7, 8, 9]) {
+        assert_eq!(AmplifierCircuit::feedback(SEVEN).run(&series), legacy_feedback_signal(SEVEN, &series),
+                   "series {:?}", series);
+    }
+}